@@ -1,9 +1,9 @@
-use std::time::Duration;
+use std::{collections::{HashMap, HashSet}, time::Duration};
 
 // Import Bevy game engine essentials
 use bevy::prelude::*;
 // Import Kira audio for Bevy to handle loading sound files
-use bevy_kira_audio::{Audio, AudioControl, AudioInstance, AudioTween};
+use bevy_kira_audio::{Audio, AudioControl, AudioInstance, AudioTween, PlaybackState};
 // Import components, resources, and events
 use crate::derivables::*;
 
@@ -16,6 +16,13 @@ impl Plugin for AudioPlugin {
 		//.add_systems(Startup, (
 		//	play_bgm,
 		//))
+		.add_systems(Startup, (
+			load_sfx,
+		))
+		// Runs after gameplay so every sound requested this frame is seen together
+		.add_systems(PostUpdate, (
+			play_sfx,
+		))
 		.add_systems(OnEnter(GameState::Menu), (
 			switch_song,
 		))
@@ -100,7 +107,7 @@ fn switch_song(
 				}
 			},
 			GameState::Win => {
-				if win_state.0 == 0 || win_state.0 == 1 {
+				if win_state.0 == 0 || win_state.0 == 1 || win_state.0 == 3 {
 					audio
 						.play(asset_server.load("bgm/lose_theme.ogg"))
 						.with_volume(BGM_VOLUME);
@@ -113,4 +120,40 @@ fn switch_song(
 			_ => (),
 		}
 	//}
+}
+
+fn load_sfx(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+) {
+	let mut sounds = HashMap::new();
+	for sfx in SfxEvent::ALL {
+		sounds.insert(sfx, asset_server.load(sfx.path()));
+	}
+	commands.insert_resource(SfxHandles{
+		sounds: sounds,
+		voices: HashMap::new(),
+	});
+}
+
+// Plays each requested sound at most once per frame, skipping it
+// if too many copies are already playing, with a slight random pitch
+fn play_sfx(
+	mut ev_r_sfx: EventReader<SfxEvent>,
+	mut sfx_handles: ResMut<SfxHandles>,
+	audio: Res<Audio>,
+) {
+	let requested: HashSet<SfxEvent> = ev_r_sfx.iter().copied().collect();
+	for sfx in requested {
+		let Some(source) = sfx_handles.sounds.get(&sfx).cloned() else {continue};
+		let voices = sfx_handles.voices.entry(sfx).or_default();
+		voices.retain(|voice| audio.state(voice) != PlaybackState::Stopped);
+		if voices.len() >= SFX_MAX_VOICES {continue};
+		let pitch = 1.0 + (rand::random::<f64>() - 0.5) * 2.0 * SFX_PITCH_VARIATION;
+		voices.push(audio
+			.play(source)
+			.with_volume(SFX_VOLUME)
+			.with_playback_rate(pitch)
+			.handle());
+	}
 }
\ No newline at end of file
@@ -0,0 +1,346 @@
+// Import Bevy game engine essentials
+use bevy::{prelude::*, math::Vec3Swizzles, sprite::Anchor};
+// Import components, resources, and events
+use crate::{derivables::*, enemy::{spawn_enemy, spawn_enemy_bullet}};
+
+// Plugin for the boss waiting at the end of each constellation,
+// it cycles through attack phases and can only be hurt by
+// hitting its weak points with the matching weapon
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_systems(Update, (
+				boss_behaviour,
+				boss_hit.after(boss_behaviour),
+				boss_animate,
+				boss_health_bar,
+			).run_if(in_state(GameState::Level))
+			.run_if(in_state(PauseState::Unpaused)))
+		;
+	}
+}
+
+pub fn spawn_boss(
+	commands: &mut Commands,
+	sprite_atlases: &SpriteAtlases,
+	tuning: &GameTuning,
+) {
+	commands
+		.spawn((SpriteSheetBundle {
+			transform: Transform::from_xyz(0.0, BOSS_ENTRY_HEIGHT, 160.0),
+			texture_atlas: sprite_atlases.boss.clone(),
+			sprite: TextureAtlasSprite{
+				index: 0,
+				custom_size: Some(Vec2::new(32.0, 32.0)),
+				..default()
+			},
+			..default()
+		},
+		Boss::new(tuning),
+		TruePosition(Vec2::new(0.0, BOSS_ENTRY_HEIGHT)),
+		AnimationTimer(Timer::from_seconds(ENEMY_ANIMATION_SPEED, TimerMode::Repeating)),
+		DespawnOnExitGameState,
+	)).with_children(|parent| {
+		for (weapon, offset) in BOSS_WEAK_POINTS {
+			parent.spawn((SpriteSheetBundle {
+				transform: Transform::from_xyz(offset.x, offset.y, 1.0),
+				texture_atlas: sprite_atlases.weak_point.clone(),
+				sprite: TextureAtlasSprite{
+					index: weapon.sprite_index(),
+					custom_size: Some(Vec2::new(8.0, 8.0)),
+					..default()
+				},
+				..default()
+			},
+			WeakPoint{
+				weapon: weapon,
+				cooldown: 0.0,
+			},
+			));
+		}
+	});
+
+	// Health bar along the bottom of the screen
+	commands
+		.spawn((SpriteBundle {
+			transform: Transform::from_xyz(-40.0, -67.0, 300.0),
+			sprite: Sprite {
+				color: COLOR_D,
+				custom_size: Some(Vec2::new(80.0, 2.0)),
+				anchor: Anchor::CenterLeft,
+				..default()
+			},
+			..default()
+		},
+		BossHealthBar,
+		DespawnOnExitGameState,
+	)).with_children(|parent| {
+		parent.spawn(SpriteBundle {
+			transform: Transform::from_xyz(0.0, 0.0, -1.0),
+			sprite: Sprite {
+				color: COLOR_B,
+				custom_size: Some(Vec2::new(80.0, 2.0)),
+				anchor: Anchor::CenterLeft,
+				..default()
+			},
+			..default()
+		});
+	});
+}
+
+// Moves from one point towards another without overshooting
+fn approach(from: Vec2, to: Vec2, step: f32) -> Vec2 {
+	let offset = to - from;
+	if offset.length() <= step {to} else {from + offset.normalize() * step}
+}
+
+fn boss_behaviour(
+	time: Res<Time>,
+	mut commands: Commands,
+	mut level_info: ResMut<LevelInfo>,
+	sprite_atlases: Res<SpriteAtlases>,
+	tuning: Res<GameTuning>,
+	mut rng: ResMut<GameRng>,
+	mut boss_query: Query<(Entity, &mut Boss, &mut TruePosition, &mut Transform)>,
+	mut star_query: Query<(&Transform, &mut Star), Without<Boss>>,
+	mech_query: Query<&TruePosition, (With<Mech>, Without<Boss>)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_camera: EventWriter<CameraEvent>,
+) {
+	for (entity, mut boss, mut pos, mut transform) in boss_query.iter_mut() {
+		// Below half health everything the boss does speeds up
+		let delta = if boss.health < tuning.boss_enrage_health {time.delta().mul_f32(tuning.boss_enrage_multiplier)} else {time.delta()};
+		let delta_seconds = delta.as_secs_f32();
+		boss.phase_timer.tick(delta);
+		boss.action_timer.tick(delta);
+
+		// Stars to attack, falling back to the mech once they are all gone
+		let targets: Vec<Vec2> = star_query.iter().map(|(star_transform, _)| star_transform.translation.xy()).collect();
+		let random_target = if targets.is_empty() {mech_query.iter().next().map(|mech_pos| mech_pos.0)}
+		else {Some(targets[rand::Rng::gen_range(&mut rng.0, 0..targets.len())])};
+		let hover = Vec2::new((boss.phase_timer.elapsed_secs() * 0.8).sin() * BOSS_HOVER_WIDTH, BOSS_HOVER_HEIGHT);
+		let mut jitter = 0.0;
+
+		match boss.phase {
+			BossPhase::Entering => {
+				pos.0 = approach(pos.0, Vec2::new(0.0, BOSS_HOVER_HEIGHT), tuning.boss_speed * delta_seconds);
+				if pos.0.y <= BOSS_HOVER_HEIGHT {
+					let next = boss.phase.next();
+					boss.set_phase(next, &tuning);
+				}
+			},
+			BossPhase::Charging => {
+				if let Some(target) = boss.target {
+					pos.0 = approach(pos.0, target, tuning.boss_charge_speed * delta_seconds);
+					if pos.0 == target {
+						boss.target = None;
+						boss.action_timer.reset();
+						for (star_transform, mut star) in star_query.iter_mut() {
+							if (star_transform.translation.xy() - pos.0).length() < BOSS_RADIUS {
+								star.damage(tuning.boss_charge_damage);
+							}
+						}
+						ev_w_camera.send(CameraEvent::Shake(BOSS_IMPACT_TRAUMA));
+						ev_w_particles.send(ParticleEvent{
+							effect: ParticleEffect::StarSpark,
+							position: pos.0,
+							direction: Vec2::ZERO,
+						});
+					}
+				} else if boss.action_timer.finished() {
+					boss.target = random_target;
+				} else {
+					// Shudder while winding up so the charge can be seen coming
+					jitter = if (boss.action_timer.elapsed_secs() / 0.05) as usize % 2 == 0 {1.0} else {-1.0};
+				}
+			},
+			BossPhase::Spawning => {
+				pos.0 = approach(pos.0, hover, tuning.boss_speed * delta_seconds);
+				if boss.action_timer.just_finished() {
+					for _ in 0..tuning.boss_minions {
+						let spec = if rand::Rng::gen::<f32>(&mut rng.0) > 0.8 {0} else {1};
+						let offset = Vec2::from_angle(rand::Rng::gen::<f32>(&mut rng.0) * std::f32::consts::TAU) * BOSS_RADIUS;
+						spawn_enemy(&mut commands, &sprite_atlases, &tuning, &mut rng, spec, pos.0 + offset);
+						ev_w_particles.send(ParticleEvent{
+							effect: ParticleEffect::SpawnWarning,
+							position: pos.0 + offset,
+							direction: offset,
+						});
+					}
+				}
+			},
+			BossPhase::Firing => {
+				pos.0 = approach(pos.0, hover, tuning.boss_speed * delta_seconds);
+				if let (true, Some(target)) = (boss.action_timer.just_finished(), random_target) {
+					spawn_enemy_bullet(&mut commands, pos.0, (target - pos.0).normalize_or_zero() * tuning.boss_shot_speed);
+					ev_w_sfx.send(SfxEvent::Pew);
+				}
+			},
+			BossPhase::Defeated => {
+				if boss.action_timer.just_finished() {
+					let offset = Vec2::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5) * 2.0 * BOSS_RADIUS;
+					ev_w_particles.send(ParticleEvent{
+						effect: ParticleEffect::EnemyBurst,
+						position: pos.0 + offset,
+						direction: Vec2::ZERO,
+					});
+					ev_w_camera.send(CameraEvent::Shake(STUN_TRAUMA));
+					ev_w_sfx.send(SfxEvent::EnemyDestroyed);
+				}
+				if boss.phase_timer.just_finished() {
+					commands.entity(entity).despawn_recursive();
+					level_info.boss_defeated = true;
+					ev_w_particles.send(ParticleEvent{
+						effect: ParticleEffect::BeamResidue,
+						position: pos.0,
+						direction: Vec2::X,
+					});
+					ev_w_camera.send(CameraEvent::Shake(BOSS_DEATH_TRAUMA));
+					ev_w_camera.send(CameraEvent::HitStop(BOSS_DEATH_HIT_STOP));
+					ev_w_sfx.send(SfxEvent::Beam);
+				}
+			},
+		}
+
+		// Attack phases only change between charges
+		if boss.phase_timer.finished() && boss.target.is_none()
+		&& boss.phase != BossPhase::Entering && boss.phase != BossPhase::Defeated {
+			let next = boss.phase.next();
+			boss.set_phase(next, &tuning);
+		}
+
+		transform.translation.x = pos.0.x.round() + jitter;
+		transform.translation.y = pos.0.y.round();
+	}
+}
+
+// Each weak point only takes damage from its own weapon,
+// anything else just bounces off the boss
+fn boss_hit(
+	time: Res<Time>,
+	mut commands: Commands,
+	mut beam_charge: ResMut<BeamCharge>,
+	mut enemies_slain: ResMut<EnemiesSlain>,
+	mut incoming_enemies: ResMut<IncomingEnemies>,
+	tuning: Res<GameTuning>,
+	mut boss_query: Query<(&mut Boss, &TruePosition)>,
+	mut weak_point_query: Query<(&GlobalTransform, &mut WeakPoint, &Parent)>,
+	slash_query: Query<(&GlobalTransform, &Direction, &Slash)>,
+	bullet_query: Query<(Entity, &TruePosition, &Bullet)>,
+	beam_query: Query<(&Transform, &Beam)>,
+	enemy_query: Query<(Entity, &TruePosition), With<Enemy>>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_camera: EventWriter<CameraEvent>,
+) {
+	// Bullets are used up on the first weak point they hit
+	let mut spent = Vec::new();
+	for (transform, mut weak_point, parent) in weak_point_query.iter_mut() {
+		weak_point.cooldown = (weak_point.cooldown - time.delta_seconds()).max(0.0);
+		let Ok((mut boss, _)) = boss_query.get_mut(parent.get()) else {continue};
+		if boss.phase == BossPhase::Defeated || weak_point.cooldown > 0.0 {continue};
+		let point = transform.translation().xy();
+		let hit = match weak_point.weapon {
+			Weapon::Slash => slash_query.iter().any(|(slash_transform, direction, slash)| {
+				let offset = (slash_transform.translation().xy() - point).abs();
+				slash.active && offset.cmplt(direction.slash_reach()).all()
+			}),
+			Weapon::Bullet => match bullet_query.iter().find(|(entity, bullet_pos, bullet)| {
+				bullet.faction == Faction::Mech && !spent.contains(entity)
+				&& (bullet_pos.0 - point).length() < BULLET_SMALL_HIT_RADIUS
+			}) {
+				Some((entity, _, _)) => {
+					commands.entity(entity).despawn_recursive();
+					spent.push(entity);
+					true
+				},
+				None => false,
+			},
+			Weapon::Beam => beam_query.iter().any(|(beam_transform, beam)| {
+				beam.contains(beam_transform.translation.xy(), point)
+			}),
+		};
+		if !hit {continue};
+
+		boss.health -= weak_point.weapon.boss_damage(&tuning);
+		boss.hurt = BOSS_HURT_FLASH;
+		weak_point.cooldown = weak_point.weapon.weak_point_cooldown();
+		if weak_point.weapon != Weapon::Beam {beam_charge.add(1.0)};
+		ev_w_sfx.send(SfxEvent::EnemyDestroyed);
+		ev_w_particles.send(ParticleEvent{
+			effect: ParticleEffect::EnemyBurst,
+			position: point,
+			direction: Vec2::ZERO,
+		});
+
+		if boss.health <= 0.0 {
+			boss.set_phase(BossPhase::Defeated, &tuning);
+			ev_w_camera.send(CameraEvent::HitStop(BOSS_DEATH_HIT_STOP));
+			// The boss takes its minions and shots down with it
+			for (entity, enemy_pos) in enemy_query.iter() {
+				enemies_slain.0 += 1;
+				commands.entity(entity).despawn_recursive();
+				ev_w_particles.send(ParticleEvent{
+					effect: ParticleEffect::EnemyBurst,
+					position: enemy_pos.0,
+					direction: Vec2::ZERO,
+				});
+			}
+			for (entity, _, bullet) in bullet_query.iter() {
+				if bullet.faction == Faction::Enemy {
+					commands.entity(entity).despawn_recursive();
+				}
+			}
+			incoming_enemies.0.clear();
+		}
+	}
+
+	// The boss's armour soaks up any bullet that touches it
+	for (_, boss_pos) in boss_query.iter() {
+		for (entity, bullet_pos, bullet) in bullet_query.iter() {
+			if bullet.faction == Faction::Mech && !spent.contains(&entity)
+			&& (bullet_pos.0 - boss_pos.0).length() < BOSS_RADIUS {
+				commands.entity(entity).despawn_recursive();
+			}
+		}
+	}
+}
+
+fn boss_animate(
+	time: Res<Time>,
+	mut boss_query: Query<(&mut Boss, &mut TextureAtlasSprite, &mut AnimationTimer)>,
+	mut weak_point_query: Query<(&WeakPoint, &mut Visibility)>,
+) {
+	for (mut boss, mut sprite, mut timer) in boss_query.iter_mut() {
+		timer.0.tick(time.delta());
+		boss.hurt = (boss.hurt - time.delta_seconds()).max(0.0);
+		let idle = if timer.0.just_finished() {(sprite.index + 1) % 2} else {sprite.index % 2};
+		sprite.index = if boss.hurt > 0.0 {3}
+		else if boss.phase == BossPhase::Charging {2}
+		else {idle};
+	}
+	// Closed weak points disappear until they can be hit again
+	for (weak_point, mut visibility) in weak_point_query.iter_mut() {
+		*visibility = if weak_point.cooldown > 0.0 {Visibility::Hidden} else {Visibility::Inherited};
+	}
+}
+
+fn boss_health_bar(
+	mut commands: Commands,
+	tuning: Res<GameTuning>,
+	boss_query: Query<&Boss>,
+	mut bar_query: Query<(Entity, &mut Sprite), With<BossHealthBar>>,
+) {
+	for boss in boss_query.iter() {
+		for (entity, mut sprite) in bar_query.iter_mut() {
+			if boss.phase == BossPhase::Defeated {
+				commands.entity(entity).despawn_recursive();
+			} else {
+				sprite.custom_size = Some(Vec2::new((80.0 * boss.health / tuning.boss_health).max(0.0).round(), 2.0));
+			}
+		}
+	}
+}
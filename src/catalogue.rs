@@ -0,0 +1,135 @@
+// Import Bevy game engine essentials
+use bevy::{prelude::*, asset::{AssetLoader, LoadContext, LoadedAsset, BoxedFuture}};
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for loading the star catalogue, a csv of real stars
+// grouped by constellation that levels can be generated from
+pub struct CataloguePlugin;
+
+impl Plugin for CataloguePlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_asset::<StarCatalogue>()
+			.init_asset_loader::<StarCatalogueLoader>()
+			.add_systems(Startup, (
+				load_catalogue,
+			))
+		;
+	}
+}
+
+#[derive(Default)]
+struct StarCatalogueLoader;
+
+impl AssetLoader for StarCatalogueLoader {
+	fn load<'a>(
+		&'a self,
+		bytes: &'a [u8],
+		load_context: &'a mut LoadContext,
+	) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+		Box::pin(async move {
+			let catalogue = parse_catalogue(std::str::from_utf8(bytes)?)?;
+			load_context.set_default_asset(LoadedAsset::new(catalogue));
+			Ok(())
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["stars.csv"]
+	}
+}
+
+// Rows are constellation,star,ra_hours,dec_degrees,magnitude after a
+// header, lines starting with # are comments
+fn parse_catalogue(text: &str) -> Result<StarCatalogue, bevy::asset::Error> {
+	let mut catalogue = StarCatalogue{
+		constellations: Vec::new(),
+	};
+	let rows = text.lines()
+		.enumerate()
+		.filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+		.skip(1);
+	for (number, line) in rows {
+		let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+		let [name, _, right_ascension, declination, magnitude] = fields[..] else {
+			return Err(bevy::asset::Error::msg(format!("line {} should have 5 fields", number + 1)));
+		};
+		let parse = |field: &str| field.parse::<f32>()
+			.map_err(|error| bevy::asset::Error::msg(format!("line {}: {}", number + 1, error)));
+		let star = CatalogueStar{
+			right_ascension: parse(right_ascension)?,
+			declination: parse(declination)?,
+			magnitude: parse(magnitude)?,
+		};
+		match catalogue.constellations.iter_mut().find(|constellation| constellation.name == name) {
+			Some(constellation) => constellation.stars.push(star),
+			None => catalogue.constellations.push(CatalogueConstellation{
+				name: name.to_string(),
+				stars: vec![star],
+			}),
+		}
+	}
+	Ok(catalogue)
+}
+
+fn load_catalogue(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+) {
+	commands.insert_resource(StarCatalogueHandle(asset_server.load(CATALOGUE_FILE)));
+}
+
+// Projects the brightest stars onto a flat map centred on the
+// constellation with east to the left like a star chart, then
+// scales it to fill the play area and joins the stars up using
+// the shortest lines that connect them all. The name, round
+// count and background are left as they are
+pub fn project_constellation(
+	catalogue_constellation: &CatalogueConstellation,
+	constellation: &mut Constellation,
+) {
+	let mut stars = catalogue_constellation.stars.clone();
+	stars.sort_by(|a, b| a.magnitude.total_cmp(&b.magnitude));
+	stars.truncate(CATALOGUE_MAX_STARS);
+	let directions: Vec<Vec3> = stars.iter().map(|star| {
+		let (right_ascension, declination) = ((star.right_ascension * 15.0).to_radians(), star.declination.to_radians());
+		Vec3::new(declination.cos() * right_ascension.cos(), declination.cos() * right_ascension.sin(), declination.sin())
+	}).collect();
+	let centre = directions.iter().sum::<Vec3>().normalize_or_zero();
+	let east = Vec3::Z.cross(centre).try_normalize().unwrap_or(Vec3::Y);
+	let north = centre.cross(east);
+	let points: Vec<Vec2> = directions.iter()
+		.map(|direction| Vec2::new(-direction.dot(east), direction.dot(north)) / direction.dot(centre))
+		.collect();
+
+	let min = points.iter().fold(Vec2::splat(f32::MAX), |min, point| min.min(*point));
+	let max = points.iter().fold(Vec2::splat(f32::MIN), |max, point| max.max(*point));
+	let extent = (max - min).max(Vec2::splat(f32::EPSILON));
+	let scale = (CATALOGUE_AREA / extent).min_element();
+	let middle = (min + max) / 2.0;
+
+	// Brightest first, so it's the dimmer of two close stars that's dropped
+	constellation.stars.clear();
+	for point in points {
+		let position = ((point - middle) * scale).floor() + 0.5;
+		if constellation.stars.iter().all(|star| star.distance(position) >= CATALOGUE_MIN_SPACING) {
+			constellation.stars.push(position);
+		}
+	}
+
+	constellation.edges.clear();
+	let mut joined = vec![false; constellation.stars.len()];
+	if let Some(first) = joined.first_mut() {*first = true};
+	for _ in 1..constellation.stars.len() {
+		let nearest = (0..joined.len()).filter(|from| joined[*from])
+			.flat_map(|from| (0..joined.len()).filter(|to| !joined[*to]).map(move |to| (from, to)))
+			.min_by(|a, b| {
+				let stars = &constellation.stars;
+				stars[a.0].distance(stars[a.1]).total_cmp(&stars[b.0].distance(stars[b.1]))
+			});
+		let Some((from, to)) = nearest else {break};
+		joined[to] = true;
+		constellation.edges.push((from, to));
+	}
+}
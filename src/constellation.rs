@@ -0,0 +1,189 @@
+// Import Bevy game engine essentials
+use bevy::{prelude::*, math::Vec3Swizzles, render::render_resource::{Extent3d, TextureDimension, TextureFormat}};
+// Import components, resources, and events
+use crate::{derivables::*, enemy::enemy_move};
+
+// Plugin for the edges joining a constellation's stars,
+// which hold it together as long as both ends survive
+pub struct ConstellationPlugin;
+
+impl Plugin for ConstellationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_systems(Update, (
+				edge_heal,
+				edge_zap.after(enemy_move),
+				edge_animate,
+			).run_if(in_state(GameState::Level))
+			.run_if(in_state(PauseState::Unpaused)))
+		;
+	}
+}
+
+// Draws the edge into its own image one pixel at a time so it
+// lines up with the pixel grid, then tints it with a palette shade
+pub fn spawn_edge(
+	commands: &mut Commands,
+	images: &mut Assets<Image>,
+	tuning: &GameTuning,
+	from: (Entity, Vec2),
+	to: (Entity, Vec2),
+) {
+	let start = from.1.floor();
+	let end = to.1.floor();
+	let min = start.min(end);
+	let size = (start - end).abs() + 1.0;
+	let (width, height) = (size.x as i32, size.y as i32);
+	let mut data = vec![0; (width * height * 4) as usize];
+
+	let (mut x, mut y) = ((start.x - min.x) as i32, (start.y - min.y) as i32);
+	let (end_x, end_y) = ((end.x - min.x) as i32, (end.y - min.y) as i32);
+	let (dx, dy) = ((end_x - x).abs(), -(end_y - y).abs());
+	let (step_x, step_y) = ((end_x - x).signum(), (end_y - y).signum());
+	let mut error = dx + dy;
+	loop {
+		// Image rows go downwards but the world goes up
+		let pixel = (((height - 1 - y) * width + x) * 4) as usize;
+		data[pixel..pixel + 4].copy_from_slice(&[255, 255, 255, 255]);
+		if x == end_x && y == end_y {break};
+		let doubled = error * 2;
+		if doubled >= dy {
+			error += dy;
+			x += step_x;
+		}
+		if doubled <= dx {
+			error += dx;
+			y += step_y;
+		}
+	}
+
+	let image = images.add(Image::new(
+		Extent3d{
+			width: width as u32,
+			height: height as u32,
+			depth_or_array_layers: 1,
+		},
+		TextureDimension::D2,
+		data,
+		TextureFormat::Rgba8UnormSrgb,
+	));
+	let centre = min + size / 2.0;
+	let mut zap_cooldown = Timer::from_seconds(tuning.edge_zap_cooldown, TimerMode::Once);
+	zap_cooldown.tick(zap_cooldown.duration());
+	commands
+		.spawn((SpriteBundle {
+			transform: Transform::from_xyz(centre.x, centre.y, 50.0),
+			texture: image,
+			sprite: Sprite {
+				color: COLOR_C,
+				custom_size: Some(size),
+				..default()
+			},
+			..default()
+		},
+		Edge{
+			stars: (from.0, to.0),
+			ends: (from.1, to.1),
+			zap_cooldown,
+		},
+		DespawnOnExitGameState,
+	));
+}
+
+// Each intact edge slowly heals both of its stars, so well
+// connected stars recover faster
+fn edge_heal(
+	time: Res<Time>,
+	tuning: Res<GameTuning>,
+	edge_query: Query<&Edge>,
+	mut star_query: Query<&mut Star>,
+) {
+	for edge in edge_query.iter() {
+		if let Ok([mut from, mut to]) = star_query.get_many_mut([edge.stars.0, edge.stars.1]) {
+			from.heal(tuning.edge_heal_rate * time.delta_seconds());
+			to.heal(tuning.edge_heal_rate * time.delta_seconds());
+		}
+	}
+}
+
+// A charged edge destroys the first enemy to cross it
+fn edge_zap(
+	mut commands: Commands,
+	time: Res<Time>,
+	sprite_atlases: Res<SpriteAtlases>,
+	mut enemies_slain: ResMut<EnemiesSlain>,
+	mut edge_query: Query<&mut Edge>,
+	enemy_query: Query<(Entity, &TruePosition), With<Enemy>>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_defeated: EventWriter<EnemyDefeated>,
+) {
+	let mut zapped = Vec::new();
+	for mut edge in edge_query.iter_mut() {
+		edge.zap_cooldown.tick(time.delta());
+		if !edge.zap_cooldown.finished() {continue};
+		let (start, end) = edge.ends;
+		let Some((entity, enemy_pos)) = enemy_query.iter()
+			.filter(|(entity, _)| !zapped.contains(entity))
+			.find(|(_, enemy_pos)| distance_to_segment(enemy_pos.0, start, end) < EDGE_ZAP_DISTANCE) else {continue};
+
+		zapped.push(entity);
+		edge.zap_cooldown.reset();
+		enemies_slain.0 += 1;
+		commands.entity(entity).despawn_recursive();
+		commands
+			.spawn((SpriteSheetBundle {
+				transform: Transform::from_xyz(enemy_pos.0.x.round(), enemy_pos.0.y.round(), 125.0),
+				texture_atlas: sprite_atlases.corpse.clone(),
+				sprite: TextureAtlasSprite{
+					index: 0,
+					custom_size: Some(Vec2::new(8.0, 8.0)),
+					..default()
+				},
+				..default()
+			},
+			Corpse(Timer::from_seconds(CORPSE_DURATION, TimerMode::Repeating)),
+			DespawnOnExitGameState,
+		));
+		ev_w_particles.send(ParticleEvent{
+			effect: ParticleEffect::EnemyBurst,
+			position: enemy_pos.0,
+			direction: Vec2::ZERO,
+		});
+		ev_w_defeated.send(EnemyDefeated{
+			position: enemy_pos.0,
+		});
+		ev_w_sfx.send(SfxEvent::EnemyDestroyed);
+	}
+}
+
+// Charged edges are bright and recharging ones dim, and an
+// edge whose star has died breaks apart
+fn edge_animate(
+	mut commands: Commands,
+	star_query: Query<&Transform, With<Star>>,
+	mut edge_query: Query<(Entity, &Transform, &Edge, &mut Sprite), Without<Star>>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+) {
+	for (entity, transform, edge, mut sprite) in edge_query.iter_mut() {
+		if star_query.get(edge.stars.0).is_err() || star_query.get(edge.stars.1).is_err() {
+			commands.entity(entity).despawn_recursive();
+			ev_w_particles.send(ParticleEvent{
+				effect: ParticleEffect::StarSpark,
+				position: transform.translation.xy(),
+				direction: Vec2::ZERO,
+			});
+			continue;
+		}
+		let shade = if edge.zap_cooldown.finished() {COLOR_C} else {COLOR_B};
+		if sprite.color != shade {
+			sprite.color = shade;
+		}
+	}
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+	let segment = end - start;
+	let along = ((point - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+	(start + segment * along).distance(point)
+}
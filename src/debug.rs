@@ -1,7 +1,12 @@
 // Import Bevy game engine essentials
-use bevy::{prelude::*, app::AppExit};
+use bevy::{prelude::*, app::AppExit, diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin}, input::InputSystem, math::Vec3Swizzles, window::ReceivedCharacter};
+use rand::SeedableRng;
 
-use crate::derivables::*;
+use crate::{derivables::*, boss::spawn_boss, catalogue::project_constellation};
+
+// Size of the overlay text grid in characters
+const OVERLAY_LINES: usize = 8;
+const OVERLAY_COLUMNS: usize = 39;
 
 // Plugin for devtools only available in the
 // debug version of the game
@@ -10,21 +15,124 @@ pub struct DebugPlugin;
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
         app
+			.add_plugins(FrameTimeDiagnosticsPlugin)
+			.insert_resource(DebugOverlay(false))
+			.insert_resource(DebugConsole{
+				open: false,
+				input: String::new(),
+				message: String::new(),
+				submitted: None,
+			})
 			.add_systems( Startup, (
-				filler,
+				spawn_overlay,
+				spawn_console,
 			//	spawn_palette,
 			))
+			// Runs straight after input is read so keys typed into
+			// the console can be hidden from the rest of the game
+			.add_systems( PreUpdate, (
+				console_input.after(InputSystem),
+			))
 			.add_systems( Update, (
+				run_console_command,
+				update_console.after(run_console_command),
 				quit_game,
+				toggle_overlay,
+				update_overlay.after(toggle_overlay),
+				draw_hitboxes.run_if(|overlay: Res<DebugOverlay>| overlay.0),
 			))
 		;
 	}
 }
 
-fn filler(
+// Debug only so these live here rather than in derivables
+#[derive(Resource)]
+struct DebugOverlay(bool);
+
+#[derive(Component)]
+struct DebugGlyph{
+	line: usize,
+	column: usize,
+}
+
+// Cheat console opened with the backquote key
+#[derive(Resource)]
+struct DebugConsole{
+	open: bool,
+	input: String,
+	message: String,
+	submitted: Option<String>,
+}
+
+#[derive(Component)]
+struct ConsoleGlyph{
+	line: usize,
+	column: usize,
+}
+
+#[derive(Component)]
+struct ConsoleBackground;
 
+fn spawn_overlay(
+	mut commands: Commands,
+	sprite_atlases: Res<SpriteAtlases>,
 ) {
+	for line in 0..OVERLAY_LINES {
+		for column in 0..OVERLAY_COLUMNS {
+			commands.spawn((
+				SpriteSheetBundle {
+					transform: Transform::from_xyz(-ORTHO_WIDTH/2.0 + 4.0 + 4.0 * column as f32, ORTHO_HEIGHT/2.0 - 5.0 - 6.0 * line as f32, 900.0),
+					texture_atlas: sprite_atlases.font.clone(),
+					sprite: TextureAtlasSprite{
+						index: 0,
+						custom_size: Some(Vec2::new(4.0, 6.0)),
+						..default()
+					},
+					visibility: Visibility::Hidden,
+					..default()
+				},
+				DebugGlyph{line, column},
+			));
+		}
+	}
+}
 
+fn spawn_console(
+	mut commands: Commands,
+	sprite_atlases: Res<SpriteAtlases>,
+) {
+	commands.spawn((
+		SpriteBundle{
+			transform: Transform::from_xyz(0.0, -ORTHO_HEIGHT/2.0 + 7.0, 890.0),
+			sprite: Sprite {
+				color: Color::BLACK,
+				custom_size: Some(Vec2::new(ORTHO_WIDTH, 14.0)),
+				..default()
+			},
+			visibility: Visibility::Hidden,
+			..default()
+		},
+		ConsoleBackground,
+	));
+	// A message line above the line being typed
+	for line in 0..2 {
+		for column in 0..OVERLAY_COLUMNS {
+			commands.spawn((
+				SpriteSheetBundle {
+					transform: Transform::from_xyz(-ORTHO_WIDTH/2.0 + 4.0 + 4.0 * column as f32, -ORTHO_HEIGHT/2.0 + 10.0 - 6.0 * line as f32, 900.0),
+					texture_atlas: sprite_atlases.font.clone(),
+					sprite: TextureAtlasSprite{
+						index: 0,
+						custom_size: Some(Vec2::new(4.0, 6.0)),
+						..default()
+					},
+					visibility: Visibility::Hidden,
+					..default()
+				},
+				ConsoleGlyph{line, column},
+			));
+		}
+	}
 }
 
 fn spawn_palette(
@@ -52,4 +160,269 @@ fn quit_game(
 	if keyboard.just_pressed(KeyCode::Escape) {
 		ev_w_exit.send(AppExit);
 	}
-}
\ No newline at end of file
+}
+
+fn toggle_overlay(
+	keyboard: Res<Input<KeyCode>>,
+	mut overlay: ResMut<DebugOverlay>,
+) {
+	if keyboard.just_pressed(KeyCode::F1) {
+		overlay.0 = !overlay.0;
+	}
+}
+
+// Writes the current game numbers into the glyph grid
+fn update_overlay(
+	overlay: Res<DebugOverlay>,
+	diagnostics: Res<DiagnosticsStore>,
+	game_state: Res<State<GameState>>,
+	pause_state: Res<State<PauseState>>,
+	level_info: Res<LevelInfo>,
+	beam_charge: Res<BeamCharge>,
+	incoming_enemies: Res<IncomingEnemies>,
+	entity_query: Query<Entity>,
+	enemy_query: Query<With<Enemy>>,
+	bullet_query: Query<With<Bullet>>,
+	star_query: Query<&Star>,
+	mut glyph_query: Query<(&mut TextureAtlasSprite, &mut Visibility, &DebugGlyph)>,
+) {
+	// Nothing is counted while the overlay is off, the glyphs just get hidden once
+	if !overlay.0 {
+		if overlay.is_changed() {
+			for (_, mut visibility, _) in glyph_query.iter_mut() {
+				*visibility = Visibility::Hidden;
+			}
+		}
+		return;
+	}
+
+	let fps = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS).and_then(|fps| fps.smoothed()).unwrap_or(0.0);
+	let stars: Vec<String> = star_query.iter().map(|star| format!("{:.0}", star.health)).collect();
+	let lines = [
+		format!("FPS {:.0} ENTITIES {}", fps, entity_query.iter().count()),
+		format!("STATE {:?} {:?}", game_state.get(), pause_state.get()),
+		format!("ROUND {} CHARGE {:.0}", level_info.round, beam_charge.0),
+		format!("ENEMIES {} INCOMING {}", enemy_query.iter().count(), incoming_enemies.0.len()),
+		format!("BULLETS {}", bullet_query.iter().count()),
+		format!("STARS {}", stars.join(" ")),
+	].map(|line| line.to_uppercase());
+
+	for (mut sprite, mut visibility, glyph) in glyph_query.iter_mut() {
+		let character = lines.get(glyph.line).and_then(|line| line.chars().nth(glyph.column)).unwrap_or(' ');
+		sprite.index = FONT_CHARS.find(character).unwrap_or(0);
+		*visibility = if character != ' ' {Visibility::Visible} else {Visibility::Hidden};
+	}
+}
+
+// Outlines every hit region using the same sizes the hit checks use
+fn draw_hitboxes(
+	mut gizmos: Gizmos,
+	tuning: Res<GameTuning>,
+	slash_query: Query<(&GlobalTransform, &Direction, &Slash)>,
+	bullet_query: Query<(&TruePosition, &Bullet)>,
+	beam_query: Query<(&Transform, &Beam)>,
+	enemy_query: Query<&TruePosition, With<Enemy>>,
+	star_query: Query<&Transform, With<Star>>,
+	boss_query: Query<&TruePosition, With<Boss>>,
+) {
+	for (transform, direction, slash) in slash_query.iter() {
+		if slash.active {
+			gizmos.rect_2d(transform.translation().xy(), 0.0, direction.slash_reach() * 2.0, COLOR_D);
+		}
+	}
+	for (pos, bullet) in bullet_query.iter() {
+		let radius = if bullet.faction == Faction::Mech {BULLET_HIT_RADIUS} else {BULLET_SMALL_HIT_RADIUS};
+		gizmos.circle_2d(pos.0, radius, COLOR_D);
+	}
+	for (transform, beam) in beam_query.iter() {
+		let size = match beam.direction {
+			Direction::Forward | Direction::Backward => beam.size,
+			Direction::Left | Direction::Right => Vec2::new(beam.size.y, beam.size.x),
+		};
+		gizmos.rect_2d(transform.translation.xy(), 0.0, size, COLOR_D);
+	}
+	for pos in enemy_query.iter() {
+		gizmos.circle_2d(pos.0, MECH_HIT_DISTANCE, COLOR_C);
+	}
+	for transform in star_query.iter() {
+		gizmos.circle_2d(transform.translation.xy(), tuning.enemy_feed_distance, COLOR_B);
+		gizmos.circle_2d(transform.translation.xy(), STAR_REPAIR_DISTANCE, COLOR_C);
+	}
+	for pos in boss_query.iter() {
+		gizmos.circle_2d(pos.0, BOSS_RADIUS, COLOR_C);
+	}
+}
+
+fn console_input(
+	mut console: ResMut<DebugConsole>,
+	mut keyboard: ResMut<Input<KeyCode>>,
+	mut ev_r_characters: EventReader<ReceivedCharacter>,
+) {
+	if keyboard.just_pressed(KeyCode::Grave) {
+		console.open = !console.open;
+		console.input.clear();
+		ev_r_characters.clear();
+	}
+	if !console.open {return};
+
+	for ev in ev_r_characters.iter() {
+		let character = ev.char.to_ascii_lowercase();
+		if character != ' ' && FONT_CHARS.contains(character.to_ascii_uppercase()) && console.input.len() < OVERLAY_COLUMNS - 2 {
+			console.input.push(character);
+		} else if character == ' ' && console.input.len() < OVERLAY_COLUMNS - 2 {
+			console.input.push(' ');
+		}
+	}
+	if keyboard.just_pressed(KeyCode::Back) {
+		console.input.pop();
+	}
+	if keyboard.just_pressed(KeyCode::Return) {
+		let command = std::mem::take(&mut console.input);
+		console.submitted = Some(command);
+	}
+	// Keep the game from reacting to anything typed
+	keyboard.reset_all();
+}
+
+fn run_console_command(
+	mut commands: Commands,
+	mut console: ResMut<DebugConsole>,
+	sprite_atlases: Res<SpriteAtlases>,
+	tuning: Res<GameTuning>,
+	mut selected_level: ResMut<SelectedLevel>,
+	mut endless: ResMut<Endless>,
+	mut level_info: ResMut<LevelInfo>,
+	mut beam_charge: ResMut<BeamCharge>,
+	mut incoming_enemies: ResMut<IncomingEnemies>,
+	mut rng: ResMut<GameRng>,
+	mut win_state: ResMut<WinState>,
+	mut level_layout: ResMut<LevelLayout>,
+	catalogue_assets: Res<Assets<StarCatalogue>>,
+	catalogue_handle: Res<StarCatalogueHandle>,
+	mech_query: Query<(Entity, Option<&Invulnerable>), With<Mech>>,
+	mut next_game_state: ResMut<NextState<GameState>>,
+) {
+	let Some(command) = console.submitted.take() else {return};
+	let words: Vec<&str> = command.split_whitespace().collect();
+	let number = |index: usize| words.get(index).and_then(|word| word.parse::<usize>().ok());
+	console.message = match words.as_slice() {
+		["level", ..] => match number(1) {
+			// Levels count from 1 like on the select screen, 6 is endless
+			Some(level) if (1..=6).contains(&level) => {
+				selected_level.0 = level - 1;
+				endless.0 = level == 6;
+				next_game_state.set(GameState::Loading);
+				format!("loading level {}", level)
+			},
+			_ => "level 1 to 6".to_string(),
+		},
+		["round", ..] => match number(1) {
+			Some(round) => {
+				// Stop short of the last round so it still ticks over and brings the boss
+				let rounds = level_layout.constellations[selected_level.0].rounds;
+				let round = if endless.0 {round} else {round.min(rounds.saturating_sub(1))};
+				level_info.round = round;
+				format!("round {}", round)
+			},
+			None => "round needs a number".to_string(),
+		},
+		["spawn", "boss", ..] => {
+			spawn_boss(&mut commands, &sprite_atlases, &tuning);
+			// Otherwise the last round brings a second one
+			level_info.boss_spawned = true;
+			"boss spawned".to_string()
+		},
+		["spawn", kind, ..] => match kind.strip_prefix("enemy_").and_then(|spec| spec.parse::<usize>().ok()) {
+			Some(spec) if spec < sprite_atlases.enemies.len() => {
+				let count = number(2).unwrap_or(1);
+				for _ in 0..count {
+					let angle = rand::Rng::gen::<f32>(&mut rng.0) * std::f32::consts::TAU;
+					incoming_enemies.0.push(IncomingEnemy{
+						spec,
+						position: Vec2::from_angle(angle) * 120.0,
+						timer: Timer::from_seconds(tuning.spawn_telegraph_duration, TimerMode::Once),
+					});
+				}
+				format!("spawning {} enemy_{}", count, spec)
+			},
+			_ => "spawn enemy_0 to enemy_2 or boss".to_string(),
+		},
+		["charge", "full"] => {
+			beam_charge.0 = BEAM_CHARGE_REQUIREMENT;
+			"charge full".to_string()
+		},
+		["charge", ..] => match number(1) {
+			Some(charge) => {
+				beam_charge.0 = 0.0;
+				beam_charge.add(charge as f32);
+				format!("charge {}", beam_charge.0)
+			},
+			None => "charge full or a number".to_string(),
+		},
+		["god"] => {
+			let mut message = "no mech".to_string();
+			for (entity, invulnerable) in mech_query.iter() {
+				if invulnerable.is_some() {
+					commands.entity(entity).remove::<Invulnerable>();
+					message = "god off".to_string();
+				} else {
+					commands.entity(entity).insert(Invulnerable(Timer::from_seconds(3600.0, TimerMode::Once)));
+					message = "god on".to_string();
+				}
+			}
+			message
+		},
+		["win"] => {
+			win_state.0 = 1;
+			next_game_state.set(GameState::Win);
+			"win".to_string()
+		},
+		["lose"] => {
+			win_state.0 = 0;
+			next_game_state.set(GameState::Win);
+			"lose".to_string()
+		},
+		["seed", ..] => match words.get(1).and_then(|word| word.parse::<u64>().ok()) {
+			Some(seed) => {
+				rng.0 = rand::rngs::StdRng::seed_from_u64(seed);
+				format!("seed {}", seed)
+			},
+			None => "seed needs a number".to_string(),
+		},
+		// Swaps the random constellation for a named one from the catalogue
+		["sky", name @ ..] if !name.is_empty() => {
+			let name = name.join(" ");
+			let found = catalogue_assets.get(&catalogue_handle.0)
+				.and_then(|catalogue| catalogue.constellations.iter().find(|constellation| constellation.name.eq_ignore_ascii_case(&name)));
+			match found {
+				Some(real) => {
+					project_constellation(real, &mut level_layout.constellations[4]);
+					format!("{} is level 5", real.name)
+				},
+				None => format!("no constellation {}", name),
+			}
+		},
+		["help"] => "level round spawn charge god win lose seed sky".to_string(),
+		[] => String::new(),
+		_ => format!("unknown command {}", words[0]),
+	};
+}
+
+fn update_console(
+	console: Res<DebugConsole>,
+	mut background_query: Query<&mut Visibility, With<ConsoleBackground>>,
+	mut glyph_query: Query<(&mut TextureAtlasSprite, &mut Visibility, &ConsoleGlyph), Without<ConsoleBackground>>,
+) {
+	for mut visibility in background_query.iter_mut() {
+		*visibility = if console.open {Visibility::Visible} else {Visibility::Hidden};
+	}
+	let lines = [
+		console.message.to_uppercase(),
+		format!("> {}_", console.input.to_uppercase()),
+	];
+	for (mut sprite, mut visibility, glyph) in glyph_query.iter_mut() {
+		let character = lines[glyph.line].chars().nth(glyph.column).unwrap_or(' ');
+		sprite.index = FONT_CHARS.find(character).unwrap_or(0);
+		*visibility = if console.open && character != ' ' {Visibility::Visible} else {Visibility::Hidden};
+	}
+}
@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+
 // Import Bevy game engine essentials
-use bevy::prelude::*;
+use bevy::{prelude::*, reflect::{TypePath, TypeUuid}};
+use bevy_kira_audio::{AudioInstance, AudioSource};
+use serde::{Deserialize, Serialize};
+use rand::rngs::StdRng;
 
 // CONSTANTS
 // Controls
@@ -23,25 +28,133 @@ pub const ALT_UP_BUTTON: KeyCode = KeyCode::W;
 pub const ALT_DOWN_BUTTON: KeyCode = KeyCode::S;
 pub const ALT_LEFT_BUTTON: KeyCode = KeyCode::A;
 pub const ALT_RIGHT_BUTTON: KeyCode = KeyCode::D;
+// Button sequences that unlock secrets when entered in order,
+// optionally only while in one game state
+pub const SECRET_SEQUENCES: [(Secret, Option<GameState>, &[SecretInput]); 2] = [
+	(Secret::Retaliate, None, &[
+		SecretInput::Up, SecretInput::Up,
+		SecretInput::Left, SecretInput::Down,
+		SecretInput::Left, SecretInput::Down,
+		SecretInput::Up, SecretInput::Left,
+		SecretInput::Up, SecretInput::Right,
+		SecretInput::Select, SecretInput::Start,
+	]),
+	// Left and right do nothing else on the title screen
+	(Secret::Editor, Some(GameState::Menu), &[
+		SecretInput::Left, SecretInput::Right,
+		SecretInput::Left, SecretInput::Right,
+		SecretInput::Left, SecretInput::Right,
+	]),
+];
 
 // Audio
 pub const BGM_VOLUME: f64 = 1.0;
 pub const SFX_VOLUME: f64 = 1.0;
+pub const SFX_MAX_VOICES: usize = 4;
+pub const SFX_PITCH_VARIATION: f64 = 0.05;
+
+// Assets
+pub const IMAGE_ASSETS: [&str; 43] = [
+	"sprites/background.png",
+	"sprites/backward_slash.png",
+	"sprites/beam_bar.png",
+	"sprites/beam_down.png",
+	"sprites/beam_left.png",
+	"sprites/beam_right.png",
+	"sprites/beam_up.png",
+	"sprites/boss.png",
+	"sprites/bullet_right.png",
+	"sprites/bullet_up.png",
+	"sprites/corpse.png",
+	"sprites/debug_font.png",
+	"sprites/digits.png",
+	"sprites/dust.png",
+	"sprites/enemy_0.png",
+	"sprites/enemy_1.png",
+	"sprites/enemy_2.png",
+	"sprites/forward_slash.png",
+	"sprites/left_slash.png",
+	"sprites/level_select.png",
+	"sprites/mech.png",
+	"sprites/pause_screen.png",
+	"sprites/pickup.png",
+	"sprites/portrait.png",
+	"sprites/right_slash.png",
+	"sprites/spawn_arrow.png",
+	"sprites/splash.png",
+	"sprites/star.png",
+	"sprites/title_screen.png",
+	"sprites/weak_point.png",
+	"sprites/win_screen.png",
+	"text/constellation.png",
+	"text/constellation_defenders.png",
+	"text/controls.png",
+	"text/hardcore.png",
+	"text/hints.png",
+	"text/loading.png",
+	"text/lost.png",
+	"text/movement.png",
+	"text/press_start.png",
+	"text/retaliation.png",
+	"text/soyscodingcafe.png",
+	"text/win.png",
+];
+pub const BGM_ASSETS: [&str; 10] = [
+	"bgm/cassiopeia.ogg",
+	"bgm/cepheus.ogg",
+	"bgm/endless.ogg",
+	"bgm/loading_theme.ogg",
+	"bgm/lose_theme.ogg",
+	"bgm/menu_theme.ogg",
+	"bgm/orion.ogg",
+	"bgm/random.ogg",
+	"bgm/ursa_minor.ogg",
+	"bgm/win_theme.ogg",
+];
+
+// Stars, edges and round counts for every constellation, the
+// level select screen always has this many
+pub const LEVEL_FILE: &str = "constellations.levels.ron";
+pub const LEVEL_SLOTS: usize = 6;
+// Real star positions the random and endless constellations are drawn
+// from, only the brightest few well spaced stars of each are kept
+pub const CATALOGUE_FILE: &str = "constellations.stars.csv";
+pub const CATALOGUE_MAX_STARS: usize = 8;
+pub const CATALOGUE_MIN_SPACING: f32 = 12.0;
+pub const CATALOGUE_AREA: Vec2 = Vec2::new(120.0, 80.0);
+
+// Level Editor
+pub const EDITOR_LEVELS: usize = 4;
+pub const EDITOR_CURSOR_SPEED: f32 = 40.0;
+pub const EDITOR_PICK_DISTANCE: f32 = 6.0;
+
+// Menus, the cursor slides to the selected item and
+// flashes, with its label written underneath
+pub const MENU_CURSOR_SPEED: f32 = 18.0;
+pub const MENU_CURSOR_FLASH: f32 = 0.4;
+pub const MENU_CURSOR_GAP: f32 = 1.0;
 
 // Window Resolution
 pub const ORTHO_WIDTH: f32 = 160.0;
 pub const ORTHO_HEIGHT: f32 = 144.0;
 
+// Characters in the debug font sheet, in order
+pub const FONT_CHARS: &str = " ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789.,:!?-+=/%_<>()";
+
+// Shown on the loading screen when a file is missing
+pub const LOADING_ERROR_TEXT: &str = "FILE MISSING  A PLAY  B BACK";
+// Shown across the win screen when the boss was beaten
+pub const BOSS_DEFEATED_TEXT: &str = "BOSS DEFEATED";
+
 // Color indexes for the palette
 //pub const COLOR_A: Color = Color::rgb(0.0, 0.0, 0.0);
-//pub const COLOR_B: Color = Color::rgb(0.3, 0.0, 0.0);
-//pub const COLOR_C: Color = Color::rgb(0.6, 0.0, 0.0);
-//pub const COLOR_D: Color = Color::rgb(1.0, 0.0, 0.0);
+pub const COLOR_B: Color = Color::rgb(0.3, 0.0, 0.0);
+pub const COLOR_C: Color = Color::rgb(0.6, 0.0, 0.0);
+pub const COLOR_D: Color = Color::rgb(1.0, 0.0, 0.0);
 
 // General Parameters
 pub const BOOT_DURATION: f32 = 1.0;
 pub const START_DURATION: f32 = 0.5;
-pub const LOADING_DURATION: f32 = 0.5;
 pub const NO_ENEMIES_DURATION: f32 = 2.0;
 pub const WIN_DURATION: f32 = 1.0;
 pub const CORPSE_DURATION: f32 = 0.05;
@@ -51,13 +164,35 @@ pub const DUST_COOLDOWN: f32 = 0.5;
 pub const DUST_SPEED: f32 = 10.0;
 pub const DUST_DURATION: f32 = 0.25;
 
-pub const TOTAL_LOAD: usize = 9;
+pub const PARTICLE_POOL_SIZE: usize = 256;
+
+// Screen Effects
+pub const MAX_SHAKE_OFFSET: f32 = 4.0;
+pub const TRAUMA_DECAY: f32 = 1.5;
+pub const BEAM_TRAUMA: f32 = 0.6;
+pub const STAR_DEATH_TRAUMA: f32 = 0.5;
+pub const STUN_TRAUMA: f32 = 0.3;
+pub const BEAM_HIT_STOP: f32 = 0.08;
+pub const STAR_DEATH_HIT_STOP: f32 = 0.12;
+pub const STUN_HIT_STOP: f32 = 0.06;
+pub const MECH_HIT_TRAUMA: f32 = 0.4;
+pub const MECH_HIT_STOP: f32 = 0.08;
 
 // Mech Stats
 pub const MECH_SPEED: f32 = 60.0;
-pub const MAX_MECH_SPEED: f32 = 30.0;
-pub const MECH_ACCELERATION: f32 = 10.0;
-pub const MECH_DAMPING: f32 = 1.0;
+// Drift movement, acceleration and damping are per second
+pub const MAX_MECH_SPEED: f32 = 60.0;
+pub const MECH_ACCELERATION: f32 = 240.0;
+pub const MECH_DAMPING: f32 = 6.0;
+
+// Double tapping a direction dashes through enemies, leaving
+// an after-image trail and a short window of invulnerability
+pub const MECH_DASH_SPEED: f32 = 200.0;
+pub const MECH_DASH_DURATION: f32 = 0.15;
+pub const MECH_DASH_COOLDOWN: f32 = 1.0;
+pub const MECH_DASH_IFRAMES: f32 = 0.3;
+pub const MECH_DASH_TRAIL_RATE: f32 = 0.03;
+pub const MECH_DOUBLE_TAP_WINDOW: f32 = 0.25;
 
 pub const STUN_COOLDOWN: f32 = 1.0;
 pub const SLASH_COOLDOWN: f32 = 0.5;
@@ -65,25 +200,146 @@ pub const SHOOT_COOLDOWN: f32 = 0.25;
 pub const BEAM_COOLDOWN: f32 = 0.8;
 
 pub const BEAM_CHARGE_REQUIREMENT: f32 = 30.0;
+// Charge needed for each beam level, and how wide and long
+// that level's beam is when fired
+pub const BEAM_CHARGE_LEVELS: [f32; 3] = [10.0, 20.0, 30.0];
+pub const BEAM_SIZES: [Vec2; 3] = [
+	Vec2::new(20.0, 48.0),
+	Vec2::new(40.0, 96.0),
+	Vec2::new(60.0, 144.0),
+];
+pub const BEAM_GAP: f32 = 8.0;
 pub const BEAM_SPEED: f32 = 0.125;
 
 pub const SLASH_SPEED: f32 = 0.05;
 pub const BULLET_SPEED: f32 = 80.0;
 
+// Hit radii for bullets, against enemies and against stars or weak points
+pub const BULLET_HIT_RADIUS: f32 = 8.0;
+pub const BULLET_SMALL_HIT_RADIUS: f32 = 6.0;
+
 pub const MECH_ANIMATION_SPEED: f32 = 0.35;
 
+// Hardcore Mech Stats
+pub const MECH_HEALTH: usize = 5;
+pub const MECH_HIT_DISTANCE: f32 = 8.0;
+pub const MECH_IFRAME_DURATION: f32 = 1.0;
+pub const MECH_IFRAME_BLINK_SPEED: f32 = 0.08;
+pub const MECH_KNOCKBACK: f32 = 12.0;
+
 // Enemy Stats
 pub const ENEMY_ANIMATION_SPEED: f32 = 0.35;
 pub const ENEMY_SPAWN_DELAY: f32 = 0.6;
+pub const SPAWN_TELEGRAPH_DURATION: f32 = 1.0;
+pub const SPAWN_INDICATOR_BLINK_SPEED: f32 = 0.15;
 
 pub const ENEMY_MAX_SPEED: f32 = 35.0;
 pub const ENEMY_ACCELERATION: f32 = 5.0;
 
+pub const ENEMY_FEED_DISTANCE: f32 = 8.0;
+pub const ENEMY_FLEE_RADIUS: f32 = 28.0;
+pub const ENEMY_ORBIT_RADIUS: f32 = 36.0;
+pub const ENEMY_AGGRO_RADIUS: f32 = 36.0;
+pub const ENEMY_SEPARATION_RADIUS: f32 = 10.0;
+pub const ENEMY_ALIGNMENT_RADIUS: f32 = 20.0;
+
+// Steering weights for each enemy spec, used when the
+// mech can't retaliate and the enemies just avoid it
+pub const ENEMY_STEERING: [SteeringWeights; 3] = [
+	SteeringWeights{seek_star: 1.0, flee_mech: 2.0, orbit_mech: 1.0, chase_mech: 0.0, separate: 1.5, align: 0.5, flank: true},
+	SteeringWeights{seek_star: 1.0, flee_mech: 2.0, orbit_mech: 1.0, chase_mech: 0.0, separate: 1.5, align: 0.5, flank: false},
+	SteeringWeights{seek_star: 1.0, flee_mech: 2.0, orbit_mech: 0.5, chase_mech: 0.0, separate: 1.5, align: 0.0, flank: false},
+];
+
+// Steering weights once the mech can retaliate, feeders
+// go after the mech to stun it while flankers circle away
+pub const ENEMY_RETALIATE_STEERING: [SteeringWeights; 3] = [
+	SteeringWeights{seek_star: 1.0, flee_mech: 2.0, orbit_mech: 1.0, chase_mech: 0.0, separate: 1.5, align: 0.5, flank: true},
+	SteeringWeights{seek_star: 1.0, flee_mech: 0.0, orbit_mech: 0.0, chase_mech: 2.0, separate: 1.5, align: 0.5, flank: false},
+	SteeringWeights{seek_star: 1.0, flee_mech: 1.0, orbit_mech: 0.0, chase_mech: 0.0, separate: 1.5, align: 0.0, flank: false},
+];
+
 pub const ENEMY_0_DPS: f32 = 25.0;
 pub const ENEMY_1_DPS: f32 = 5.0;
+pub const ENEMY_2_DPS: f32 = 0.0;
+
+pub const ENEMY_2_RANGE: f32 = 40.0;
+pub const ENEMY_FIRE_RATE: f32 = 1.5;
+pub const ENEMY_BULLET_SPEED: f32 = 35.0;
+pub const ENEMY_BULLET_DAMAGE: f32 = 5.0;
+pub const BULLET_REFLECT_MULTIPLIER: f32 = 1.5;
+
+// Boss Stats
+pub const BOSS_HEALTH: f32 = 60.0;
+pub const BOSS_RADIUS: f32 = 12.0;
+pub const BOSS_SPEED: f32 = 20.0;
+pub const BOSS_ENTRY_HEIGHT: f32 = 100.0;
+pub const BOSS_HOVER_HEIGHT: f32 = 36.0;
+pub const BOSS_HOVER_WIDTH: f32 = 40.0;
+pub const BOSS_PHASE_DURATION: f32 = 6.0;
+pub const BOSS_ENRAGE_HEALTH: f32 = 30.0;
+pub const BOSS_ENRAGE_MULTIPLIER: f32 = 1.5;
+
+pub const BOSS_WIND_UP: f32 = 0.8;
+pub const BOSS_CHARGE_SPEED: f32 = 100.0;
+pub const BOSS_CHARGE_DAMAGE: f32 = 30.0;
+pub const BOSS_IMPACT_TRAUMA: f32 = 0.5;
+pub const BOSS_SPAWN_RATE: f32 = 1.5;
+pub const BOSS_MINIONS: usize = 2;
+pub const BOSS_FIRE_RATE: f32 = 0.6;
+pub const BOSS_SHOT_SPEED: f32 = 40.0;
+
+pub const BOSS_SLASH_DAMAGE: f32 = 4.0;
+pub const BOSS_BULLET_DAMAGE: f32 = 1.0;
+pub const BOSS_BEAM_DAMAGE: f32 = 15.0;
+pub const BOSS_HURT_FLASH: f32 = 0.1;
+pub const WEAK_POINT_BULLET_COOLDOWN: f32 = 0.1;
+
+pub const BOSS_DEATH_DURATION: f32 = 1.5;
+pub const BOSS_DEATH_BURST_RATE: f32 = 0.15;
+pub const BOSS_DEATH_TRAUMA: f32 = 1.0;
+pub const BOSS_DEATH_HIT_STOP: f32 = 0.2;
+
+// Where the boss can be hurt and by what
+pub const BOSS_WEAK_POINTS: [(Weapon, Vec2); 4] = [
+	(Weapon::Slash, Vec2::new(0.0, -13.0)),
+	(Weapon::Bullet, Vec2::new(-13.0, 0.0)),
+	(Weapon::Bullet, Vec2::new(13.0, 0.0)),
+	(Weapon::Beam, Vec2::new(0.0, 0.0)),
+];
+
+// Pickup Stats
+pub const PICKUP_DROP_CHANCE: f32 = 0.08;
+pub const PICKUP_LIFETIME: f32 = 6.0;
+pub const PICKUP_BLINK_TIME: f32 = 2.0;
+pub const PICKUP_BLINK_SPEED: f32 = 0.1;
+pub const PICKUP_RADIUS: f32 = 8.0;
+pub const PICKUP_BEAM_CHARGE: f32 = 10.0;
+pub const PICKUP_STAR_HEAL: f32 = 20.0;
+
+pub const BUFF_DURATION: f32 = 6.0;
+pub const BUFF_BLINK_TIME: f32 = 1.5;
+pub const RAPID_FIRE_MULTIPLIER: f32 = 2.0;
+pub const RAPID_FIRE_BULLET_MULTIPLIER: f32 = 1.5;
+pub const SPEED_BOOST_MULTIPLIER: f32 = 1.5;
 
 // Star Stats
 pub const STAR_HEALTH: f32 = 80.0;
+pub const STAR_REGEN_RATE: f32 = 1.0;
+pub const STAR_REPAIR_RATE: f32 = 8.0;
+pub const STAR_ADJACENT_DISTANCE: f32 = 16.0;
+pub const STAR_REPAIR_DISTANCE: f32 = 8.0;
+pub const STAR_SHIELD_COST: f32 = 10.0;
+pub const STAR_SHIELD_DURATION: f32 = 5.0;
+pub const STAR_SHIELD_SHIMMER: f32 = 0.2;
+pub const STAR_LAST_STAND_HEALTH: f32 = 20.0;
+pub const STAR_LAST_STAND_RADIUS: f32 = 32.0;
+pub const STAR_LAST_STAND_TRAUMA: f32 = 0.5;
+// Intact edges between stars heal both ends and zap the first
+// enemy to cross them, then need time to recharge
+pub const EDGE_HEAL_RATE: f32 = 0.5;
+pub const EDGE_ZAP_DISTANCE: f32 = 3.0;
+pub const EDGE_ZAP_COOLDOWN: f32 = 6.0;
 
 // STATES
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
@@ -95,6 +351,7 @@ pub enum GameState {
 	Loading,
 	Level,
 	Win,
+	Editor,
 }
 
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
@@ -104,7 +361,75 @@ pub enum PauseState {
 	Paused,
 }
 
+// EVENTS
+#[derive(Event, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SfxEvent {
+	Beam,
+	EnemyDestroyed,
+	Pew,
+	Secret,
+	Slash,
+	UiSelect,
+	Unstun,
+}
+
+impl SfxEvent {
+	pub const ALL: [SfxEvent; 7] = [
+		SfxEvent::Beam,
+		SfxEvent::EnemyDestroyed,
+		SfxEvent::Pew,
+		SfxEvent::Secret,
+		SfxEvent::Slash,
+		SfxEvent::UiSelect,
+		SfxEvent::Unstun,
+	];
+
+	pub fn path(&self) -> &'static str {
+		match self {
+			SfxEvent::Beam => "sfx/beam.ogg",
+			SfxEvent::EnemyDestroyed => "sfx/enemy_destroyed.ogg",
+			SfxEvent::Pew => "sfx/pew.ogg",
+			SfxEvent::Secret => "sfx/secret.ogg",
+			SfxEvent::Slash => "sfx/slash.ogg",
+			SfxEvent::UiSelect => "sfx/ui_select.ogg",
+			SfxEvent::Unstun => "sfx/unstun.ogg",
+		}
+	}
+}
+
+#[derive(Event, Clone, Copy)]
+pub enum CameraEvent {
+	Shake(f32),
+	HitStop(f32),
+}
+
+#[derive(Event, Clone, Copy)]
+pub struct EnemyDefeated {
+	pub position: Vec2,
+}
+
+// Sent when a secret sequence has been entered
+#[derive(Event, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SecretEvent(pub Secret);
+
+// Sent by a menu when an item is picked or the menu is backed out
+// of, the screen that owns the menu decides what happens
+#[derive(Event, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct MenuEvent {
+	pub menu: Entity,
+	pub action: MenuAction,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MenuAction {
+	Confirm(usize),
+	Cancel,
+}
+
 // COMPONENTS
+#[derive(Component)]
+pub struct MainCamera;
+
 #[derive(Component)]
 pub struct DespawnOnExitGameState;
 
@@ -114,12 +439,42 @@ pub struct DespawnOnExitPauseState;
 #[derive(Component)]
 pub struct StartText;
 
+#[derive(Component)]
+pub struct ControlsText;
+
+#[derive(Component)]
+pub struct MovementText;
+
+// Screen effect options on the title screen, written in the debug font
+#[derive(Component)]
+pub struct OptionGlyph{
+	pub line: usize,
+	pub column: usize,
+}
+
 #[derive(Component)]
 pub struct LoadingText;
 
+// Shown over the loading screen when an asset fails to load
+#[derive(Component)]
+pub struct LoadingError;
+
 #[derive(Component)]
 pub struct HintText;
 
+#[derive(Component)]
+pub struct EditorStar;
+
+#[derive(Component)]
+pub struct EditorBackground;
+
+// One character of the level editor's top or bottom line
+#[derive(Component)]
+pub struct EditorGlyph{
+	pub line: usize,
+	pub column: usize,
+}
+
 #[derive(Component)]
 pub struct WinText;
 
@@ -132,6 +487,20 @@ pub struct Mech{
 	pub slash_cooldown: Timer,
 	pub shoot_cooldown: Timer,
 	pub beam_cooldown: Timer,
+	pub health: usize,
+	pub iframes: Timer,
+	pub dash: Timer,
+	pub dash_cooldown: Timer,
+	pub dash_trail: Timer,
+	pub double_tap: Timer,
+	pub last_tap: Option<Direction>,
+}
+
+impl Mech {
+	// Dashing passes through enemies and their shots
+	pub fn dashing(&self) -> bool {
+		!self.dash.finished()
+	}
 }
 
 #[derive(Component)]
@@ -142,10 +511,65 @@ pub struct Slash{
 #[derive(Component)]
 pub struct Star{
 	pub health: f32,
+	pub max_health: f32,
+	pub shield: f32,
+	pub last_stand: bool,
 }
 
+impl Star {
+	pub fn new(health: f32) -> Self {
+		Self {
+			health,
+			max_health: health,
+			shield: 0.0,
+			last_stand: false,
+		}
+	}
+
+	pub fn shielded(&self) -> bool {
+		self.shield > 0.0
+	}
+
+	// Shielded stars ignore all damage
+	pub fn damage(&mut self, amount: f32) {
+		if !self.shielded() {
+			self.health = (self.health - amount).clamp(0.0, 100.0);
+		}
+	}
+
+	pub fn heal(&mut self, amount: f32) {
+		if self.health > 0.0 {
+			self.health = (self.health + amount).clamp(0.0, self.max_health);
+		}
+	}
+}
+
+// A line between two stars of a constellation, it breaks
+// for good as soon as either of its stars dies
+#[derive(Component)]
+pub struct Edge{
+	pub stars: (Entity, Entity),
+	pub ends: (Vec2, Vec2),
+	pub zap_cooldown: Timer,
+}
+
+// A fired beam, width is across the direction and length along it
 #[derive(Component)]
-pub struct Beam(pub Direction);
+pub struct Beam {
+	pub direction: Direction,
+	pub size: Vec2,
+}
+
+impl Beam {
+	// Checks a point against the beam's rectangle in its own frame
+	// so the hit test always matches the geometry that was fired
+	pub fn contains(&self, centre: Vec2, point: Vec2) -> bool {
+		let facing = self.direction.vector();
+		let offset = point - centre;
+		offset.dot(facing).abs() < self.size.y / 2.0
+		&& offset.perp_dot(facing).abs() < self.size.x / 2.0
+	}
+}
 
 #[derive(Component)]
 pub struct BeamBar;
@@ -155,8 +579,26 @@ pub struct Enemy {
 	pub spec: usize,
 	pub rotation: f32,
 	pub dps: f32,
+	pub range: f32,
 }
 
+// How strongly each steering behaviour pulls an enemy,
+// flanking enemies target the star furthest from the mech
+#[derive(Deserialize, Clone, Copy)]
+pub struct SteeringWeights {
+	pub seek_star: f32,
+	pub flee_mech: f32,
+	pub orbit_mech: f32,
+	pub chase_mech: f32,
+	pub separate: f32,
+	pub align: f32,
+	pub flank: bool,
+}
+
+// Cooldown between shots for enemies that attack from range
+#[derive(Component)]
+pub struct Shooter(pub Timer);
+
 #[derive(Component)]
 pub struct Velocity(pub Vec2);
 
@@ -166,16 +608,393 @@ pub struct Corpse(pub Timer);
 #[derive(Component)]
 pub struct Dust(pub Timer);
 
+#[derive(Component)]
+pub struct Particle {
+	pub active: bool,
+	pub effect: ParticleEffect,
+	pub velocity: Vec2,
+	pub lifetime: Timer,
+}
+
+#[derive(Event, Clone, Copy)]
+pub struct ParticleEvent {
+	pub effect: ParticleEffect,
+	pub position: Vec2,
+	pub direction: Vec2,
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum ParticleEffect {
+	StarSpark,
+	EnemyBurst,
+	BeamResidue,
+	SpawnWarning,
+	StarShield,
+}
+
+// Everything needed to describe how a particle effect
+// looks and moves, colors are shades from the palette
+// in the order they are used over the particle's life
+pub struct ParticleSpec {
+	pub count: usize,
+	pub speed: f32,
+	pub speed_variation: f32,
+	pub spread: f32,
+	pub length: f32,
+	pub lifetime: f32,
+	pub drag: f32,
+	pub size: f32,
+	pub shades: &'static [Color],
+}
+
+impl ParticleEffect {
+	pub fn spec(&self) -> ParticleSpec {
+		match self {
+			ParticleEffect::StarSpark => ParticleSpec {
+				count: 6,
+				speed: 30.0,
+				speed_variation: 15.0,
+				spread: 180.0,
+				length: 0.0,
+				lifetime: 0.3,
+				drag: 4.0,
+				size: 1.0,
+				shades: &[COLOR_D, COLOR_C],
+			},
+			ParticleEffect::EnemyBurst => ParticleSpec {
+				count: 8,
+				speed: 40.0,
+				speed_variation: 20.0,
+				spread: 180.0,
+				length: 0.0,
+				lifetime: 0.35,
+				drag: 5.0,
+				size: 1.0,
+				shades: &[COLOR_D, COLOR_C, COLOR_B],
+			},
+			ParticleEffect::BeamResidue => ParticleSpec {
+				count: 24,
+				speed: 6.0,
+				speed_variation: 4.0,
+				spread: 180.0,
+				length: 144.0,
+				lifetime: 0.9,
+				drag: 1.0,
+				size: 1.0,
+				shades: &[COLOR_D, COLOR_C, COLOR_B],
+			},
+			ParticleEffect::SpawnWarning => ParticleSpec {
+				count: 3,
+				speed: 12.0,
+				speed_variation: 4.0,
+				spread: 30.0,
+				length: 0.0,
+				lifetime: 0.4,
+				drag: 2.0,
+				size: 2.0,
+				shades: &[COLOR_D, COLOR_C],
+			},
+			ParticleEffect::StarShield => ParticleSpec {
+				count: 4,
+				speed: 10.0,
+				speed_variation: 2.0,
+				spread: 180.0,
+				length: 0.0,
+				lifetime: 0.4,
+				drag: 0.0,
+				size: 1.0,
+				shades: &[COLOR_C, COLOR_B],
+			},
+		}
+	}
+}
+
+#[derive(Component)]
+pub struct Pickup {
+	pub kind: PickupKind,
+	pub lifetime: Timer,
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum PickupKind {
+	BeamCharge,
+	RapidFire,
+	SpeedBoost,
+	StarHeal,
+	Invulnerable,
+	StarShield,
+}
+
+impl PickupKind {
+	pub const ALL: [PickupKind; 6] = [
+		PickupKind::BeamCharge,
+		PickupKind::RapidFire,
+		PickupKind::SpeedBoost,
+		PickupKind::StarHeal,
+		PickupKind::Invulnerable,
+		PickupKind::StarShield,
+	];
+
+	// Index into the pickup sprite sheet
+	pub fn sprite_index(&self) -> usize {
+		match self {
+			PickupKind::BeamCharge => 0,
+			PickupKind::RapidFire => 1,
+			PickupKind::SpeedBoost => 2,
+			PickupKind::StarHeal => 3,
+			PickupKind::Invulnerable => 4,
+			PickupKind::StarShield => 5,
+		}
+	}
+}
+
+// Timed buffs added to the mech by pickups
+#[derive(Component, Deref, DerefMut)]
+pub struct RapidFire(pub Timer);
+
+#[derive(Component, Deref, DerefMut)]
+pub struct SpeedBoost(pub Timer);
+
+#[derive(Component, Deref, DerefMut)]
+pub struct Invulnerable(pub Timer);
+
+#[derive(Component)]
+pub struct BuffIcon(pub PickupKind);
+
+#[derive(Component)]
+pub struct HealthPip(pub usize);
+
+#[derive(Component)]
+pub struct HardcoreText;
+
+// Edge of screen arrow for one of the eight directions
+// enemies can arrive from, its child shows how many
+#[derive(Component)]
+pub struct SpawnIndicator(pub usize);
+
+#[derive(Component)]
+pub struct SpawnCount;
+
+#[derive(Component)]
+pub struct Boss {
+	pub health: f32,
+	pub hurt: f32,
+	pub phase: BossPhase,
+	pub phase_timer: Timer,
+	pub action_timer: Timer,
+	pub target: Option<Vec2>,
+}
+
+impl Boss {
+	pub fn new(tuning: &GameTuning) -> Self {
+		Self {
+			health: tuning.boss_health,
+			hurt: 0.0,
+			phase: BossPhase::Entering,
+			phase_timer: BossPhase::Entering.phase_timer(tuning),
+			action_timer: BossPhase::Entering.action_timer(tuning),
+			target: None,
+		}
+	}
+
+	pub fn set_phase(&mut self, phase: BossPhase, tuning: &GameTuning) {
+		self.phase = phase;
+		self.phase_timer = phase.phase_timer(tuning);
+		self.action_timer = phase.action_timer(tuning);
+		self.target = None;
+	}
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum BossPhase {
+	Entering,
+	Charging,
+	Spawning,
+	Firing,
+	Defeated,
+}
+
+impl BossPhase {
+	// Attack phases loop until the boss is defeated
+	pub fn next(&self) -> BossPhase {
+		match self {
+			BossPhase::Entering => BossPhase::Charging,
+			BossPhase::Charging => BossPhase::Spawning,
+			BossPhase::Spawning => BossPhase::Firing,
+			BossPhase::Firing => BossPhase::Charging,
+			BossPhase::Defeated => BossPhase::Defeated,
+		}
+	}
+
+	pub fn phase_timer(&self, tuning: &GameTuning) -> Timer {
+		match self {
+			BossPhase::Defeated => Timer::from_seconds(BOSS_DEATH_DURATION, TimerMode::Once),
+			_ => Timer::from_seconds(tuning.boss_phase_duration, TimerMode::Once),
+		}
+	}
+
+	// How often the phase's attack happens, for charging
+	// this is the wind up before each charge
+	pub fn action_timer(&self, tuning: &GameTuning) -> Timer {
+		match self {
+			BossPhase::Charging => Timer::from_seconds(tuning.boss_wind_up, TimerMode::Once),
+			BossPhase::Spawning => Timer::from_seconds(tuning.boss_spawn_rate, TimerMode::Repeating),
+			BossPhase::Firing => Timer::from_seconds(tuning.boss_fire_rate, TimerMode::Repeating),
+			BossPhase::Defeated => Timer::from_seconds(BOSS_DEATH_BURST_RATE, TimerMode::Repeating),
+			BossPhase::Entering => Timer::from_seconds(0.0, TimerMode::Once),
+		}
+	}
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Weapon {
+	Slash,
+	Bullet,
+	Beam,
+}
+
+impl Weapon {
+	// Index into the weak point sprite sheet
+	pub fn sprite_index(&self) -> usize {
+		match self {
+			Weapon::Slash => 0,
+			Weapon::Bullet => 1,
+			Weapon::Beam => 2,
+		}
+	}
+
+	pub fn boss_damage(&self, tuning: &GameTuning) -> f32 {
+		match self {
+			Weapon::Slash => tuning.boss_slash_damage,
+			Weapon::Bullet => tuning.boss_bullet_damage,
+			Weapon::Beam => tuning.boss_beam_damage,
+		}
+	}
+
+	// Slashes and beams stay out for several frames so a
+	// weak point closes until the attack has finished
+	pub fn weak_point_cooldown(&self) -> f32 {
+		match self {
+			Weapon::Slash => SLASH_SPEED * 8.0,
+			Weapon::Bullet => WEAK_POINT_BULLET_COOLDOWN,
+			Weapon::Beam => BEAM_SPEED * 8.0,
+		}
+	}
+}
+
+#[derive(Component)]
+pub struct WeakPoint {
+	pub weapon: Weapon,
+	pub cooldown: f32,
+}
+
+#[derive(Component)]
+pub struct BossHealthBar;
+
 #[derive(Component)]
 pub struct Bullet {
 	pub velocity: Vec2,
+	pub faction: Faction,
+}
+
+// Who fired a bullet, reflected bullets change sides
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Faction {
+	Mech,
+	Enemy,
+}
+
+// A list or grid of items navigated with the d-pad, items are
+// laid out row by row with the given number of columns
+#[derive(Component)]
+pub struct MenuWidget {
+	pub items: Vec<MenuItem>,
+	pub columns: usize,
+	pub wrap: bool,
+	pub selected: usize,
+	// Where the cursor is drawn as it slides between items
+	pub cursor: Rect,
 }
 
+impl MenuWidget {
+	pub fn new(items: Vec<MenuItem>, columns: usize, wrap: bool) -> Self {
+		let selected = items.iter().position(|item| item.enabled).unwrap_or(0);
+		let cursor = items.get(selected).map(|item| item.rect).unwrap_or_default();
+		Self{
+			items,
+			columns: columns.max(1),
+			wrap,
+			selected,
+			cursor,
+		}
+	}
+
+	// Steps over disabled items, returns whether the selection moved
+	pub fn step(&mut self, offset: isize) -> bool {
+		let total = self.items.len() as isize;
+		let mut index = self.selected as isize;
+		for _ in 0..total {
+			index += offset;
+			if self.wrap {
+				index = index.rem_euclid(total);
+			} else if !(0..total).contains(&index) {
+				return false;
+			}
+			if self.items[index as usize].enabled {
+				let moved = index as usize != self.selected;
+				self.selected = index as usize;
+				return moved;
+			}
+		}
+		false
+	}
+}
+
+#[derive(Clone)]
+pub struct MenuItem {
+	pub rect: Rect,
+	pub label: String,
+	pub enabled: bool,
+}
+
+impl MenuItem {
+	pub fn new(rect: Rect) -> Self {
+		Self{
+			rect,
+			label: String::new(),
+			enabled: true,
+		}
+	}
+
+	pub fn with_label(mut self, label: &str) -> Self {
+		self.label = label.to_uppercase();
+		self
+	}
+
+	pub fn with_enabled(mut self, enabled: bool) -> Self {
+		self.enabled = enabled;
+		self
+	}
+}
+
+// One of the four sides of a menu's cursor frame
+#[derive(Component)]
+pub struct MenuCursor(pub usize);
+
+#[derive(Component)]
+pub struct MenuLabel;
+
+#[derive(Component)]
+pub struct MenuGlyph(pub usize);
+
 #[derive(Component)]
-pub struct PauseHighlight;
+pub struct TitleMenu;
 
 #[derive(Component)]
-pub struct SelectHighlight;
+pub struct SelectMenu;
+
+#[derive(Component)]
+pub struct PauseMenu;
 
 #[derive(Component)]
 pub struct TruePosition(pub Vec2);
@@ -188,9 +1007,100 @@ pub enum Direction {
 	Right,
 }
 
+impl Direction {
+	// Half size of a slash hitbox, wider than it is deep
+	pub fn slash_reach(&self) -> Vec2 {
+		match self {
+			Direction::Forward | Direction::Backward => Vec2::new(16.0, 12.0),
+			Direction::Left | Direction::Right => Vec2::new(12.0, 16.0),
+		}
+	}
+
+	// Closest facing for a movement vector, diagonals keep
+	// the current facing if it is one of the two components
+	pub fn nearest(vector: Vec2, current: Direction) -> Direction {
+		let vertical = if vector.y > 0.0 {Direction::Backward} else {Direction::Forward};
+		let horizontal = if vector.x < 0.0 {Direction::Left} else {Direction::Right};
+		if vector.x == 0.0 {
+			vertical
+		} else if vector.y == 0.0 || current == horizontal {
+			horizontal
+		} else if current == vertical {
+			vertical
+		} else {
+			horizontal
+		}
+	}
+
+	pub fn vector(&self) -> Vec2 {
+		match self {
+			Direction::Forward => Vec2::NEG_Y,
+			Direction::Backward => Vec2::Y,
+			Direction::Left => Vec2::NEG_X,
+			Direction::Right => Vec2::X,
+		}
+	}
+}
+
 #[derive(Component)]
 pub struct AnimationTimer(pub Timer);
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Secret {
+	Retaliate,
+	Editor,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SecretInput {
+	Up,
+	Down,
+	Left,
+	Right,
+	Select,
+	Start,
+}
+
+impl SecretInput {
+	pub const ALL: [SecretInput; 6] = [
+		SecretInput::Up,
+		SecretInput::Down,
+		SecretInput::Left,
+		SecretInput::Right,
+		SecretInput::Select,
+		SecretInput::Start,
+	];
+
+	pub fn just_pressed(&self, keyboard: &Input<KeyCode>) -> bool {
+		match self {
+			SecretInput::Up => keyboard.any_just_pressed([UP_BUTTON, ALT_UP_BUTTON]),
+			SecretInput::Down => keyboard.any_just_pressed([DOWN_BUTTON, ALT_DOWN_BUTTON]),
+			SecretInput::Left => keyboard.any_just_pressed([LEFT_BUTTON, ALT_LEFT_BUTTON]),
+			SecretInput::Right => keyboard.any_just_pressed([RIGHT_BUTTON, ALT_RIGHT_BUTTON]),
+			SecretInput::Select => keyboard.any_just_pressed([SELECT_BUTTON, ALT_SELECT_BUTTON, ALT_ALT_SELECT_BUTTON]),
+			SecretInput::Start => keyboard.any_just_pressed([START_BUTTON, ALT_START_BUTTON, ALT_ALT_START_BUTTON]),
+		}
+	}
+}
+
+pub struct SecretSequence {
+	pub secret: Secret,
+	pub state: Option<GameState>,
+	pub inputs: Vec<SecretInput>,
+	pub progress: usize,
+}
+
+impl SecretSequences {
+	pub fn new(sequences: &[(Secret, Option<GameState>, &[SecretInput])]) -> Self {
+		SecretSequences(sequences.iter().map(|(secret, state, inputs)| SecretSequence{
+			secret: *secret,
+			state: *state,
+			inputs: inputs.to_vec(),
+			progress: 0,
+		}).collect())
+	}
+}
+
 // RESOURCES
 #[derive(Resource)]
 pub struct SelectedLevel(pub usize);
@@ -204,11 +1114,24 @@ pub struct Retaliate(pub bool);
 #[derive(Resource)]
 pub struct Milky(pub bool);
 
+// The mech has limited health and can be destroyed
+#[derive(Resource)]
+pub struct Hardcore(pub bool);
+
+// Control scheme, eight way lets the mech move diagonally
+#[derive(Resource)]
+pub struct EightWay(pub bool);
+
+// Movement model, drift gives the mech acceleration and momentum
+#[derive(Resource)]
+pub struct Drift(pub bool);
+
 #[derive(Resource)]
 pub struct LoadTimes(pub bool);
 
+// Every secret sequence along with how far into it the player is
 #[derive(Resource)]
-pub struct SecretCode(pub usize);
+pub struct SecretSequences(pub Vec<SecretSequence>);
 
 #[derive(Resource)]
 pub struct EnemiesSlain(pub usize);
@@ -216,6 +1139,16 @@ pub struct EnemiesSlain(pub usize);
 #[derive(Resource)]
 pub struct BeamCharge(pub f32);
 
+impl BeamCharge {
+	// Charge stops at a full bar so it can't be banked between beams
+	pub fn add(&mut self, amount: f32) {
+		self.0 = (self.0 + amount).min(BEAM_CHARGE_REQUIREMENT);
+	}
+}
+
+// Which ending to show: 0 constellation lost, 1 defended by
+// beating the boss, 2 lost without a fight, 3 mech destroyed
+// in hardcore
 #[derive(Resource)]
 pub struct WinState(pub usize);
 
@@ -225,6 +1158,195 @@ pub struct WinSpawned(pub usize);
 #[derive(Resource)]
 pub struct DustTimer(pub Timer);
 
+#[derive(Resource)]
+pub struct ParticlePool(pub Vec<Entity>);
+
+// The star the mech is currently standing on, if any
+#[derive(Resource)]
+pub struct MechOnStar(pub Option<Entity>);
+
+// Neighbour radii for enemy flocking
+#[derive(Resource)]
+pub struct FlockingSettings{
+	pub separation_radius: f32,
+	pub alignment_radius: f32,
+}
+
+// Enemy positions and velocities bucketed into square cells
+// so neighbours can be found without checking every enemy
+#[derive(Resource)]
+pub struct EnemyGrid{
+	pub cell_size: f32,
+	pub cells: HashMap<IVec2, Vec<(Vec2, Vec2)>>,
+}
+
+impl EnemyGrid {
+	fn cell(&self, position: Vec2) -> IVec2 {
+		(position / self.cell_size).floor().as_ivec2()
+	}
+
+	pub fn insert(&mut self, position: Vec2, velocity: Vec2) {
+		let cell = self.cell(position);
+		self.cells.entry(cell).or_default().push((position, velocity));
+	}
+
+	// Positions and velocities of everything within the radius
+	pub fn nearby(&self, position: Vec2, radius: f32) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+		let min = self.cell(position - Vec2::splat(radius));
+		let max = self.cell(position + Vec2::splat(radius));
+		(min.x..=max.x)
+			.flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+			.filter_map(|cell| self.cells.get(&cell))
+			.flatten()
+			.copied()
+			.filter(move |(neighbour, _)| (*neighbour - position).length() < radius)
+	}
+}
+
+// Balance numbers loaded from assets/balance.tuning.ron, anything
+// missing from the file falls back to the constants above
+#[derive(Resource, Deserialize, TypeUuid, TypePath, Clone)]
+#[uuid = "6f1c2a8e-4b7d-4e59-9a3c-2d8f0b5e7c41"]
+#[serde(default)]
+pub struct GameTuning{
+	pub mech_speed: f32,
+	pub max_mech_speed: f32,
+	pub mech_acceleration: f32,
+	pub mech_damping: f32,
+	pub stun_cooldown: f32,
+	pub slash_cooldown: f32,
+	pub shoot_cooldown: f32,
+	pub beam_cooldown: f32,
+	pub bullet_speed: f32,
+	pub dash_speed: f32,
+	pub dash_cooldown: f32,
+	pub enemy_dps: [f32; 3],
+	pub enemy_max_speed: f32,
+	pub enemy_acceleration: f32,
+	pub enemy_fire_rate: f32,
+	pub enemy_bullet_speed: f32,
+	pub enemy_bullet_damage: f32,
+	pub enemy_spawn_delay: f32,
+	pub spawn_telegraph_duration: f32,
+	pub star_health: f32,
+	pub star_regen_rate: f32,
+	pub star_repair_rate: f32,
+	pub edge_heal_rate: f32,
+	pub edge_zap_cooldown: f32,
+	pub enemy_range: [f32; 3],
+	pub enemy_feed_distance: f32,
+	pub enemy_flee_radius: f32,
+	pub enemy_orbit_radius: f32,
+	pub enemy_aggro_radius: f32,
+	pub enemy_steering: [SteeringWeights; 3],
+	pub enemy_retaliate_steering: [SteeringWeights; 3],
+	pub boss_health: f32,
+	pub boss_speed: f32,
+	pub boss_phase_duration: f32,
+	pub boss_enrage_health: f32,
+	pub boss_enrage_multiplier: f32,
+	pub boss_wind_up: f32,
+	pub boss_charge_speed: f32,
+	pub boss_charge_damage: f32,
+	pub boss_spawn_rate: f32,
+	pub boss_minions: usize,
+	pub boss_fire_rate: f32,
+	pub boss_shot_speed: f32,
+	pub boss_slash_damage: f32,
+	pub boss_bullet_damage: f32,
+	pub boss_beam_damage: f32,
+	pub pickup_drop_chance: f32,
+	pub pickup_lifetime: f32,
+	pub pickup_beam_charge: f32,
+	pub pickup_star_heal: f32,
+	pub buff_duration: f32,
+	pub rapid_fire_multiplier: f32,
+	pub rapid_fire_bullet_multiplier: f32,
+	pub speed_boost_multiplier: f32,
+	pub star_shield_cost: f32,
+	pub star_shield_duration: f32,
+}
+
+impl Default for GameTuning {
+	fn default() -> Self {
+		Self {
+			mech_speed: MECH_SPEED,
+			max_mech_speed: MAX_MECH_SPEED,
+			mech_acceleration: MECH_ACCELERATION,
+			mech_damping: MECH_DAMPING,
+			stun_cooldown: STUN_COOLDOWN,
+			slash_cooldown: SLASH_COOLDOWN,
+			shoot_cooldown: SHOOT_COOLDOWN,
+			beam_cooldown: BEAM_COOLDOWN,
+			bullet_speed: BULLET_SPEED,
+			dash_speed: MECH_DASH_SPEED,
+			dash_cooldown: MECH_DASH_COOLDOWN,
+			enemy_dps: [ENEMY_0_DPS, ENEMY_1_DPS, ENEMY_2_DPS],
+			enemy_max_speed: ENEMY_MAX_SPEED,
+			enemy_acceleration: ENEMY_ACCELERATION,
+			enemy_fire_rate: ENEMY_FIRE_RATE,
+			enemy_bullet_speed: ENEMY_BULLET_SPEED,
+			enemy_bullet_damage: ENEMY_BULLET_DAMAGE,
+			enemy_spawn_delay: ENEMY_SPAWN_DELAY,
+			spawn_telegraph_duration: SPAWN_TELEGRAPH_DURATION,
+			star_health: STAR_HEALTH,
+			star_regen_rate: STAR_REGEN_RATE,
+			star_repair_rate: STAR_REPAIR_RATE,
+			edge_heal_rate: EDGE_HEAL_RATE,
+			edge_zap_cooldown: EDGE_ZAP_COOLDOWN,
+			enemy_range: [0.0, 0.0, ENEMY_2_RANGE],
+			enemy_feed_distance: ENEMY_FEED_DISTANCE,
+			enemy_flee_radius: ENEMY_FLEE_RADIUS,
+			enemy_orbit_radius: ENEMY_ORBIT_RADIUS,
+			enemy_aggro_radius: ENEMY_AGGRO_RADIUS,
+			enemy_steering: ENEMY_STEERING,
+			enemy_retaliate_steering: ENEMY_RETALIATE_STEERING,
+			boss_health: BOSS_HEALTH,
+			boss_speed: BOSS_SPEED,
+			boss_phase_duration: BOSS_PHASE_DURATION,
+			boss_enrage_health: BOSS_ENRAGE_HEALTH,
+			boss_enrage_multiplier: BOSS_ENRAGE_MULTIPLIER,
+			boss_wind_up: BOSS_WIND_UP,
+			boss_charge_speed: BOSS_CHARGE_SPEED,
+			boss_charge_damage: BOSS_CHARGE_DAMAGE,
+			boss_spawn_rate: BOSS_SPAWN_RATE,
+			boss_minions: BOSS_MINIONS,
+			boss_fire_rate: BOSS_FIRE_RATE,
+			boss_shot_speed: BOSS_SHOT_SPEED,
+			boss_slash_damage: BOSS_SLASH_DAMAGE,
+			boss_bullet_damage: BOSS_BULLET_DAMAGE,
+			boss_beam_damage: BOSS_BEAM_DAMAGE,
+			pickup_drop_chance: PICKUP_DROP_CHANCE,
+			pickup_lifetime: PICKUP_LIFETIME,
+			pickup_beam_charge: PICKUP_BEAM_CHARGE,
+			pickup_star_heal: PICKUP_STAR_HEAL,
+			buff_duration: BUFF_DURATION,
+			rapid_fire_multiplier: RAPID_FIRE_MULTIPLIER,
+			rapid_fire_bullet_multiplier: RAPID_FIRE_BULLET_MULTIPLIER,
+			speed_boost_multiplier: SPEED_BOOST_MULTIPLIER,
+			star_shield_cost: STAR_SHIELD_COST,
+			star_shield_duration: STAR_SHIELD_DURATION,
+		}
+	}
+}
+
+#[derive(Resource)]
+pub struct TuningHandle(pub Handle<GameTuning>);
+
+#[derive(Resource)]
+pub struct CameraEffects{
+	pub trauma: f32,
+	pub hit_stop: Timer,
+}
+
+// Player options for screen effects, shake scales
+// how much trauma is added and hit stop can be turned off
+#[derive(Resource)]
+pub struct ScreenEffectSettings{
+	pub shake: f32,
+	pub hit_stop: bool,
+}
+
 #[derive(Resource)]
 pub struct BootTimer(pub Timer);
 
@@ -232,12 +1354,16 @@ pub struct BootTimer(pub Timer);
 pub struct StartTimer(pub Timer);
 
 #[derive(Resource)]
-pub struct LoadingTimer(pub Timer);
+pub struct LoadingProgress {
+	pub total: usize,
+	pub loaded: usize,
+	pub failed: usize,
+}
 
 #[derive(Resource)]
-pub struct LoadingLength {
-	pub total: usize,
-	pub current: usize,
+pub struct GameAssets{
+	pub images: Vec<Handle<Image>>,
+	pub bgm: Vec<Handle<AudioSource>>,
 }
 
 #[derive(Resource)]
@@ -246,15 +1372,143 @@ pub struct NoEnemies(pub Timer);
 #[derive(Resource)]
 pub struct WinTimer(pub Timer);
 
+// Random numbers for waves, enemies, the boss and pickups,
+// can be reseeded to replay a run
+#[derive(Resource)]
+pub struct GameRng(pub StdRng);
+
+// Enemies waiting to arrive, shown at the screen edge until they spawn
+#[derive(Resource)]
+pub struct IncomingEnemies(pub Vec<IncomingEnemy>);
+
+pub struct IncomingEnemy{
+	pub spec: usize,
+	pub position: Vec2,
+	pub timer: Timer,
+}
+
 #[derive(Resource)]
 pub struct LevelInfo{
 	pub round_timer: Timer,
 	pub round: usize,
+	pub boss_spawned: bool,
+	pub boss_defeated: bool,
 }
 
-#[derive(Resource)]
+// Every constellation in level select order, loaded from the
+// level file and written back to it by the level editor
+#[derive(Resource, Deserialize, Serialize, TypeUuid, TypePath, Clone, Default)]
+#[uuid = "b3e8d1f4-7a26-4c0b-8e5d-91f2a6c4d073"]
 pub struct LevelLayout{
-	pub constellations: Vec<Vec<Vec2>>,
+	pub constellations: Vec<Constellation>,
+}
+
+// Edges join two stars by their index, a background of None
+// picks one of the night skies at random each time
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct Constellation{
+	pub name: String,
+	pub stars: Vec<Vec2>,
+	pub edges: Vec<(usize, usize)>,
+	pub rounds: usize,
+	pub background: Option<usize>,
+}
+
+#[derive(Resource)]
+pub struct LevelLayoutHandle(pub Handle<LevelLayout>);
+
+// Every constellation read from the star catalogue file
+#[derive(TypeUuid, TypePath)]
+#[uuid = "4d9a7c02-e1b5-4f38-a6d7-5c0e83b2f916"]
+pub struct StarCatalogue{
+	pub constellations: Vec<CatalogueConstellation>,
+}
+
+pub struct CatalogueConstellation{
+	pub name: String,
+	pub stars: Vec<CatalogueStar>,
+}
+
+// Right ascension in hours and declination in degrees
+#[derive(Clone, Copy)]
+pub struct CatalogueStar{
+	pub right_ascension: f32,
+	pub declination: f32,
+	pub magnitude: f32,
+}
+
+#[derive(Resource)]
+pub struct StarCatalogueHandle(pub Handle<StarCatalogue>);
+
+// Which authored constellation the level editor is changing,
+// the edits themselves go straight into LevelLayout
+#[derive(Resource)]
+pub struct LevelEditor{
+	pub level: usize,
+	pub cursor: Vec2,
+	pub grabbed: Option<usize>,
+	pub linking: Option<usize>,
+	pub message: String,
+}
+
+#[derive(Resource)]
+pub struct SpriteAtlases{
+	pub mech: Handle<TextureAtlas>,
+	pub forward_slash: Handle<TextureAtlas>,
+	pub backward_slash: Handle<TextureAtlas>,
+	pub left_slash: Handle<TextureAtlas>,
+	pub right_slash: Handle<TextureAtlas>,
+	pub beam_down: Handle<TextureAtlas>,
+	pub beam_up: Handle<TextureAtlas>,
+	pub beam_left: Handle<TextureAtlas>,
+	pub beam_right: Handle<TextureAtlas>,
+	pub boss: Handle<TextureAtlas>,
+	pub weak_point: Handle<TextureAtlas>,
+	pub spawn_arrow: Handle<TextureAtlas>,
+	pub digits: Handle<TextureAtlas>,
+	pub font: Handle<TextureAtlas>,
+	pub enemies: Vec<Handle<TextureAtlas>>,
+	pub corpse: Handle<TextureAtlas>,
+	pub dust: Handle<TextureAtlas>,
+	pub pickup: Handle<TextureAtlas>,
+	pub star: Handle<TextureAtlas>,
+	pub background: Handle<TextureAtlas>,
+	pub beam_bar: Handle<TextureAtlas>,
+	pub hints: Handle<TextureAtlas>,
+	pub loading_text: Handle<TextureAtlas>,
+	pub retaliation_text: Handle<TextureAtlas>,
+	pub controls_text: Handle<TextureAtlas>,
+	pub movement_text: Handle<TextureAtlas>,
+	pub lost_text: Handle<TextureAtlas>,
+	pub constellation_text: Handle<TextureAtlas>,
+	pub win_text: Handle<TextureAtlas>,
+}
+
+impl SpriteAtlases {
+	pub fn slash(&self, direction: Direction) -> Handle<TextureAtlas> {
+		match direction {
+			Direction::Forward => self.forward_slash.clone(),
+			Direction::Backward => self.backward_slash.clone(),
+			Direction::Left => self.left_slash.clone(),
+			Direction::Right => self.right_slash.clone(),
+		}
+	}
+
+	pub fn beam(&self, direction: Direction) -> Handle<TextureAtlas> {
+		match direction {
+			Direction::Forward => self.beam_down.clone(),
+			Direction::Backward => self.beam_up.clone(),
+			Direction::Left => self.beam_left.clone(),
+			Direction::Right => self.beam_right.clone(),
+		}
+	}
+}
+
+#[derive(Resource)]
+pub struct SfxHandles{
+	pub sounds: HashMap<SfxEvent, Handle<AudioSource>>,
+	pub voices: HashMap<SfxEvent, Vec<Handle<AudioInstance>>>,
 }
 
 //#[derive(Resource)]
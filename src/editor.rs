@@ -0,0 +1,323 @@
+// Import Bevy game engine essentials
+use bevy::prelude::*;
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for the level editor, unlocked with a secret on the
+// title screen, for placing stars and joining them with edges
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.insert_resource(LevelEditor{
+				level: 0,
+				cursor: Vec2::ZERO,
+				grabbed: None,
+				linking: None,
+				message: String::new(),
+			})
+			.add_systems(Update, (
+				open_editor,
+			).run_if(in_state(GameState::Menu)))
+			.add_systems(OnEnter(GameState::Editor), (
+				spawn_editor,
+			))
+			.add_systems(Update, (
+				editor_input,
+				editor_stars.after(editor_input),
+				editor_text.after(editor_input),
+				draw_editor.after(editor_input),
+			).run_if(in_state(GameState::Editor)))
+		;
+	}
+}
+
+fn open_editor(
+	mut ev_r_secret: EventReader<SecretEvent>,
+	mut next_game_state: ResMut<NextState<GameState>>,
+) {
+	for _ in ev_r_secret.iter().filter(|ev| ev.0 == Secret::Editor) {
+		next_game_state.set(GameState::Editor);
+	}
+}
+
+fn spawn_editor(
+	mut commands: Commands,
+	sprite_atlases: Res<SpriteAtlases>,
+	mut editor: ResMut<LevelEditor>,
+) {
+	editor.cursor = Vec2::ZERO;
+	editor.grabbed = None;
+	editor.linking = None;
+	editor.message = "A ADD B DEL SELECT LINK START SAVE".to_string();
+
+	commands
+		.spawn((SpriteSheetBundle {
+			transform: Transform::from_xyz(0.0, 0.0, 0.0),
+			texture_atlas: sprite_atlases.background.clone(),
+			sprite: TextureAtlasSprite{
+				index: 1,
+				custom_size: Some(Vec2::new(160.0, 144.0)),
+				..default()
+			},
+			..default()
+		},
+		EditorBackground,
+		DespawnOnExitGameState,
+	));
+	// The constellation's details along the top, messages along the bottom
+	for line in 0..2 {
+		for column in 0..39 {
+			commands.spawn((
+				SpriteSheetBundle {
+					transform: Transform::from_xyz(-76.0 + 4.0 * column as f32, 67.0 - 134.0 * line as f32, 900.0),
+					texture_atlas: sprite_atlases.font.clone(),
+					sprite: TextureAtlasSprite{
+						index: 0,
+						custom_size: Some(Vec2::new(4.0, 6.0)),
+						..default()
+					},
+					..default()
+				},
+				EditorGlyph{line, column},
+				DespawnOnExitGameState,
+			));
+		}
+	}
+}
+
+fn editor_input(
+	keyboard: Res<Input<KeyCode>>,
+	time: Res<Time>,
+	mut editor: ResMut<LevelEditor>,
+	mut level_layout: ResMut<LevelLayout>,
+	mut selected_level: ResMut<SelectedLevel>,
+	mut endless: ResMut<Endless>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut next_game_state: ResMut<NextState<GameState>>,
+) {
+	let mut direction = Vec2::ZERO;
+	if keyboard.any_pressed([UP_BUTTON, ALT_UP_BUTTON]) {direction.y += 1.0}
+	if keyboard.any_pressed([DOWN_BUTTON, ALT_DOWN_BUTTON]) {direction.y -= 1.0}
+	if keyboard.any_pressed([LEFT_BUTTON, ALT_LEFT_BUTTON]) {direction.x -= 1.0}
+	if keyboard.any_pressed([RIGHT_BUTTON, ALT_RIGHT_BUTTON]) {direction.x += 1.0}
+	if direction != Vec2::ZERO {
+		// Moving the cursor alone isn't a change worth rebuilding for
+		let bounds = Vec2::new(ORTHO_WIDTH, ORTHO_HEIGHT) / 2.0 - 8.0;
+		let cursor = (editor.cursor + direction * EDITOR_CURSOR_SPEED * time.delta_seconds()).clamp(-bounds, bounds);
+		editor.bypass_change_detection().cursor = cursor;
+	}
+
+	// Stars sit in the middle of a pixel like the authored ones
+	let point = editor.cursor.floor() + 0.5;
+	let level = editor.level;
+	// Only mark the layout changed on an actual edit so the
+	// stars and text aren't rebuilt every frame
+	let mut edited = false;
+	let constellation = &mut level_layout.bypass_change_detection().constellations[level];
+	if let Some(star) = editor.grabbed.and_then(|index| constellation.stars.get_mut(index)) {
+		if *star != point {
+			*star = point;
+			edited = true;
+		}
+	}
+	let hovered = constellation.stars.iter()
+		.position(|star| star.distance(point) < EDITOR_PICK_DISTANCE);
+
+	if keyboard.any_just_pressed([A_BUTTON, ALT_A_BUTTON]) {
+		if editor.grabbed.is_some() {
+			editor.grabbed = None;
+			editor.message = "PLACED".to_string();
+		} else if hovered.is_some() {
+			editor.grabbed = hovered;
+			editor.message = "MOVING".to_string();
+		} else {
+			constellation.stars.push(point);
+			edited = true;
+			editor.message = format!("ADDED STAR {}", constellation.stars.len());
+		}
+		ev_w_sfx.send(SfxEvent::UiSelect);
+	} else if keyboard.any_just_pressed([B_BUTTON, ALT_B_BUTTON]) && editor.grabbed.is_none() {
+		if let Some(index) = hovered {
+			// Edges refer to stars by index so shift the later ones down
+			constellation.stars.remove(index);
+			constellation.edges.retain(|(a, b)| *a != index && *b != index);
+			for (a, b) in constellation.edges.iter_mut() {
+				if *a > index {*a -= 1}
+				if *b > index {*b -= 1}
+			}
+			editor.linking = None;
+			edited = true;
+			editor.message = "REMOVED STAR".to_string();
+			ev_w_sfx.send(SfxEvent::UiSelect);
+		}
+	} else if keyboard.any_just_pressed([SELECT_BUTTON, ALT_SELECT_BUTTON, ALT_ALT_SELECT_BUTTON]) {
+		match (editor.linking, hovered) {
+			(None, Some(index)) => {
+				editor.linking = Some(index);
+				editor.message = "LINK TO?".to_string();
+			},
+			(Some(from), Some(to)) if from != to => {
+				let edge = (from.min(to), from.max(to));
+				if let Some(existing) = constellation.edges.iter().position(|(a, b)| (*a).min(*b) == edge.0 && (*a).max(*b) == edge.1) {
+					constellation.edges.remove(existing);
+					editor.message = "UNLINKED".to_string();
+				} else {
+					constellation.edges.push(edge);
+					editor.message = "LINKED".to_string();
+				}
+				editor.linking = None;
+				edited = true;
+			},
+			_ => editor.linking = None,
+		}
+		ev_w_sfx.send(SfxEvent::UiSelect);
+	} else if keyboard.any_just_pressed([KeyCode::Minus, KeyCode::Equals, KeyCode::BracketLeft, KeyCode::BracketRight]) {
+		let step = if keyboard.any_just_pressed([KeyCode::BracketLeft, KeyCode::BracketRight]) {10} else {1};
+		constellation.rounds = if keyboard.any_just_pressed([KeyCode::Minus, KeyCode::BracketLeft]) {
+			constellation.rounds.saturating_sub(step).max(1)
+		} else {
+			constellation.rounds + step
+		};
+		edited = true;
+	} else if keyboard.just_pressed(KeyCode::G) {
+		// Random, then each of the night skies in turn
+		constellation.background = match constellation.background {
+			None => Some(0),
+			Some(background) if background < 4 => Some(background + 1),
+			Some(_) => None,
+		};
+		edited = true;
+	} else if keyboard.just_pressed(KeyCode::Tab) {
+		editor.level = (level + 1) % EDITOR_LEVELS;
+		editor.grabbed = None;
+		editor.linking = None;
+		editor.message = String::new();
+	} else if keyboard.just_pressed(KeyCode::P) {
+		selected_level.0 = level;
+		endless.0 = false;
+		next_game_state.set(GameState::Loading);
+	} else if keyboard.any_just_pressed([START_BUTTON, ALT_START_BUTTON, ALT_ALT_START_BUTTON]) {
+		editor.message = save_levels(level_layout.as_ref());
+		ev_w_sfx.send(SfxEvent::Secret);
+	} else if keyboard.just_pressed(KeyCode::Back) {
+		next_game_state.set(GameState::Menu);
+	}
+	if edited {
+		level_layout.set_changed();
+	}
+}
+
+// Writes the level file back into the assets folder, the random and
+// endless constellations' stars are left out since the menu makes new ones
+#[cfg(not(target_arch = "wasm32"))]
+fn save_levels(
+	level_layout: &LevelLayout,
+) -> String {
+	let mut layout = level_layout.clone();
+	for generated in layout.constellations.iter_mut().skip(EDITOR_LEVELS) {
+		generated.stars.clear();
+		generated.edges.clear();
+	}
+	let path = bevy::asset::FileAssetIo::get_base_path().join("assets").join(LEVEL_FILE);
+	let saved = ron::ser::to_string_pretty(&layout, ron::ser::PrettyConfig::default().indentor("\t".to_string()))
+		.map_err(|error| error.to_string())
+		.and_then(|text| std::fs::write(&path, text).map_err(|error| error.to_string()));
+	match saved {
+		Ok(()) => "SAVED".to_string(),
+		Err(error) => {
+			warn!("Couldn't save {}: {}", path.display(), error);
+			"SAVE FAILED".to_string()
+		},
+	}
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_levels(
+	_level_layout: &LevelLayout,
+) -> String {
+	"NO SAVING ON THE WEB".to_string()
+}
+
+// Rebuilds the star sprites whenever the constellation changes
+fn editor_stars(
+	mut commands: Commands,
+	sprite_atlases: Res<SpriteAtlases>,
+	editor: Res<LevelEditor>,
+	level_layout: Res<LevelLayout>,
+	star_query: Query<Entity, With<EditorStar>>,
+	mut background_query: Query<&mut TextureAtlasSprite, With<EditorBackground>>,
+) {
+	if !editor.is_changed() && !level_layout.is_changed() {return};
+
+	let constellation = &level_layout.constellations[editor.level];
+	for entity in star_query.iter() {
+		commands.entity(entity).despawn_recursive();
+	}
+	for star_loc in &constellation.stars {
+		commands
+			.spawn((SpriteSheetBundle {
+				transform: Transform::from_xyz(star_loc.x, star_loc.y, 100.0),
+				texture_atlas: sprite_atlases.star.clone(),
+				sprite: TextureAtlasSprite{
+					index: 0,
+					custom_size: Some(Vec2::new(15.0, 15.0)),
+					..default()
+				},
+				..default()
+			},
+			EditorStar,
+			DespawnOnExitGameState,
+		));
+	}
+	for mut sprite in background_query.iter_mut() {
+		sprite.index = constellation.background.unwrap_or(1);
+	}
+}
+
+fn editor_text(
+	editor: Res<LevelEditor>,
+	level_layout: Res<LevelLayout>,
+	mut glyph_query: Query<(&mut TextureAtlasSprite, &mut Visibility, &EditorGlyph)>,
+) {
+	if !editor.is_changed() && !level_layout.is_changed() {return};
+
+	let constellation = &level_layout.constellations[editor.level];
+	let background = match constellation.background {
+		Some(background) => background.to_string(),
+		None => "RANDOM".to_string(),
+	};
+	let lines = [
+		format!("{} ROUNDS {} BG {}", constellation.name, constellation.rounds, background).to_uppercase(),
+		editor.message.to_uppercase(),
+	];
+	for (mut sprite, mut visibility, glyph) in glyph_query.iter_mut() {
+		let character = lines[glyph.line].chars().nth(glyph.column).unwrap_or(' ');
+		sprite.index = FONT_CHARS.find(character).unwrap_or(0);
+		*visibility = if character != ' ' {Visibility::Visible} else {Visibility::Hidden};
+	}
+}
+
+// Edges, the cursor, and whichever stars are being pointed at
+fn draw_editor(
+	mut gizmos: Gizmos,
+	editor: Res<LevelEditor>,
+	level_layout: Res<LevelLayout>,
+) {
+	let constellation = &level_layout.constellations[editor.level];
+	for (a, b) in constellation.edges.iter() {
+		if let (Some(start), Some(end)) = (constellation.stars.get(*a), constellation.stars.get(*b)) {
+			gizmos.line_2d(*start, *end, COLOR_C);
+		}
+	}
+	if let Some(star) = editor.linking.and_then(|index| constellation.stars.get(index)) {
+		gizmos.circle_2d(*star, EDITOR_PICK_DISTANCE, COLOR_C);
+	}
+	let point = editor.cursor.floor() + 0.5;
+	if let Some(star) = constellation.stars.iter().find(|star| star.distance(point) < EDITOR_PICK_DISTANCE) {
+		gizmos.circle_2d(*star, EDITOR_PICK_DISTANCE + 2.0, COLOR_D);
+	}
+	gizmos.line_2d(point - Vec2::X * 3.0, point + Vec2::X * 3.0, COLOR_D);
+	gizmos.line_2d(point - Vec2::Y * 3.0, point + Vec2::Y * 3.0, COLOR_D);
+}
@@ -1,8 +1,7 @@
 // Import Bevy game engine essentials
 use bevy::{prelude::*, math::Vec3Swizzles};
-use bevy_kira_audio::{Audio, AudioControl};
 // Import components, resources, and events
-use crate::derivables::*;
+use crate::{derivables::*, steering};
 
 pub struct EnemyPlugin;
 
@@ -10,10 +9,13 @@ impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app
 			.add_systems(Update,(
+				update_enemy_grid.before(enemy_move),
 				enemy_move,
 				slash_enemy,
 				shoot_enemy,
 				beam_enemy,
+				enemy_shoot.after(enemy_move),
+				enemy_bullet_hit,
 				enemy_animate,
 				advance_corpses,
 			).run_if(in_state(GameState::Level))
@@ -22,35 +24,92 @@ impl Plugin for EnemyPlugin {
 	}
 }
 
+// Used by both the round spawner and anything else that summons enemies
+pub fn spawn_enemy(
+	commands: &mut Commands,
+	sprite_atlases: &SpriteAtlases,
+	tuning: &GameTuning,
+	rng: &mut GameRng,
+	spec: usize,
+	position: Vec2,
+) {
+	let mut enemy = commands
+		.spawn((SpriteSheetBundle {
+			transform: Transform::from_xyz(position.x.round(), position.y.round(), 150.0),
+			texture_atlas: sprite_atlases.enemies[spec].clone(),
+			sprite: TextureAtlasSprite{
+				index: 0,
+				custom_size: Some(Vec2::new(16.0, 16.0)),
+				..default()
+			},
+			..default()
+		},
+		Enemy{
+			spec: spec,
+			rotation: if rand::Rng::gen::<f32>(&mut rng.0) > 0.5 {1.0} else {-1.0},
+			dps: tuning.enemy_dps[spec],
+			range: tuning.enemy_range[spec],
+		},
+		Velocity(Vec2::ZERO),
+		TruePosition(position),
+		AnimationTimer(Timer::from_seconds(ENEMY_ANIMATION_SPEED, TimerMode::Repeating)),
+		DespawnOnExitGameState,
+	));
+	if spec == 2 {
+		enemy.insert(Shooter(Timer::from_seconds(tuning.enemy_fire_rate, TimerMode::Repeating)));
+	}
+}
+
+// Enemy bullets are plain squares so they stand out from the mech's
+pub fn spawn_enemy_bullet(
+	commands: &mut Commands,
+	position: Vec2,
+	velocity: Vec2,
+) {
+	commands
+		.spawn((SpriteBundle {
+			transform: Transform::from_xyz(position.x.round(), position.y.round(), 150.0),
+			sprite: Sprite {
+				color: COLOR_D,
+				custom_size: Some(Vec2::new(3.0, 3.0)),
+				..default()
+			},
+			..default()
+		},
+		Bullet{
+			velocity: velocity,
+			faction: Faction::Enemy,
+		},
+		TruePosition(position),
+		DespawnOnExitGameState,
+	));
+}
+
 fn slash_enemy(
 	mut commands: Commands,
 	mut enemies_slain: ResMut<EnemiesSlain>,
 	mut beam_charge: ResMut<BeamCharge>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
+	sprite_atlases: Res<SpriteAtlases>,
 	slash_query: Query<(&GlobalTransform, &Direction, &Slash)>,
-	enemy_query: Query<(Entity, &Transform, &TruePosition, With<Enemy>)>
+	enemy_query: Query<(Entity, &Transform, &TruePosition, With<Enemy>)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_defeated: EventWriter<EnemyDefeated>,
 ) {
 	for (slash_transform, direction, slash) in slash_query.iter() {
 		let mut hit_sfx = false;
 		if slash.active {
 			for (entity, transform, pos, _) in enemy_query.iter() {
-				let mut hit = false;
 				let offset = (slash_transform.translation().xy() - pos.0).abs();
-				match direction {
-					Direction::Forward | Direction::Backward => if offset.x < 16.0 && offset.y < 12.0 {hit = true},
-					Direction::Left | Direction::Right => if offset.x < 12.0 && offset.y < 16.0 {hit = true},
-				}
-				if hit {
+				if offset.cmplt(direction.slash_reach()).all() {
 					enemies_slain.0 += 1;
-					beam_charge.0 += 1.0;
+					beam_charge.add(1.0);
 					commands.entity(entity).despawn_recursive();
 					hit_sfx = true;
 					commands
 						.spawn((SpriteSheetBundle {
 							transform: Transform::from_xyz(transform.translation.x, transform.translation.y, 125.0),
-							texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/corpse.png"), Vec2::new(8.0, 8.0), 4, 2, None, None)).clone(),
+							texture_atlas: sprite_atlases.corpse.clone(),
 							sprite: TextureAtlasSprite{
 								index: 0,
 								custom_size: Some(Vec2::new(8.0, 8.0)),
@@ -61,10 +120,18 @@ fn slash_enemy(
 						Corpse(Timer::from_seconds(CORPSE_DURATION, TimerMode::Repeating)),
 						DespawnOnExitGameState,
 					));
+					ev_w_particles.send(ParticleEvent{
+						effect: ParticleEffect::EnemyBurst,
+						position: pos.0,
+						direction: Vec2::ZERO,
+					});
+					ev_w_defeated.send(EnemyDefeated{
+						position: pos.0,
+					});
 				}
 			}
 		}
-		if hit_sfx {audio.play(asset_server.load("sfx/enemy_destroyed.ogg")).with_volume(SFX_VOLUME);}
+		if hit_sfx {ev_w_sfx.send(SfxEvent::EnemyDestroyed);}
 	}
 }
 
@@ -72,24 +139,26 @@ fn shoot_enemy(
 	mut commands: Commands,
 	mut enemies_slain: ResMut<EnemiesSlain>,
 	mut beam_charge: ResMut<BeamCharge>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
-	bullet_query: Query<(&TruePosition, With<Bullet>)>,
-	enemy_query: Query<(Entity, &TruePosition, &Transform, With<Enemy>)>
+	sprite_atlases: Res<SpriteAtlases>,
+	bullet_query: Query<(&TruePosition, &Bullet)>,
+	enemy_query: Query<(Entity, &TruePosition, &Transform, With<Enemy>)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_defeated: EventWriter<EnemyDefeated>,
 ) {
-	for (bullet_pos, _) in bullet_query.iter() {
+	for (bullet_pos, bullet) in bullet_query.iter() {
+		if bullet.faction != Faction::Mech {continue};
 		let mut hit_sfx = false;
 		for (entity, enemy_pos, enemy_transform, _) in enemy_query.iter() {
-			if (bullet_pos.0 - enemy_pos.0).length() < 8.0 {
+			if (bullet_pos.0 - enemy_pos.0).length() < BULLET_HIT_RADIUS {
 				enemies_slain.0 += 1;
-				beam_charge.0 += 1.0;
+				beam_charge.add(1.0);
 				commands.entity(entity).despawn_recursive();
 				hit_sfx = true;
 				commands
 					.spawn((SpriteSheetBundle {
 						transform: Transform::from_xyz(enemy_transform.translation.x, enemy_transform.translation.y, 125.0),
-						texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/corpse.png"), Vec2::new(8.0, 8.0), 4, 2, None, None)).clone(),
+						texture_atlas: sprite_atlases.corpse.clone(),
 						sprite: TextureAtlasSprite{
 							index: 0,
 							custom_size: Some(Vec2::new(8.0, 8.0)),
@@ -100,37 +169,41 @@ fn shoot_enemy(
 					Corpse(Timer::from_seconds(CORPSE_DURATION, TimerMode::Repeating)),
 					DespawnOnExitGameState,
 				));
+				ev_w_particles.send(ParticleEvent{
+					effect: ParticleEffect::EnemyBurst,
+					position: enemy_pos.0,
+					direction: Vec2::ZERO,
+				});
+				ev_w_defeated.send(EnemyDefeated{
+					position: enemy_pos.0,
+				});
 			}
 		}
-		if hit_sfx {audio.play(asset_server.load("sfx/enemy_destroyed.ogg")).with_volume(SFX_VOLUME);}
+		if hit_sfx {ev_w_sfx.send(SfxEvent::EnemyDestroyed);}
 	}
 }
 
 fn beam_enemy(
 	mut commands: Commands,
 	mut enemies_slain: ResMut<EnemiesSlain>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
+	sprite_atlases: Res<SpriteAtlases>,
 	beam_query: Query<(&Transform, &Beam)>,
-	enemy_query: Query<(Entity, &TruePosition, &Transform, With<Enemy>)>
+	enemy_query: Query<(Entity, &TruePosition, &Transform, With<Enemy>)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_defeated: EventWriter<EnemyDefeated>,
 ) {
 	for (transform, beam) in beam_query.iter() {
 		let mut hit_sfx = false;
 		for (entity, pos, enemy_transform, _) in enemy_query.iter() {
-			let offset = match beam.0 {
-				Direction::Forward | Direction::Backward => Vec2::new(30.0, 72.0),
-				Direction::Left | Direction::Right=> Vec2::new(72.0, 30.0),
-			};
-			if (transform.translation.x - pos.0.x).abs() < offset.x 
-			&& (transform.translation.y - pos.0.y).abs() < offset.y {
+			if beam.contains(transform.translation.xy(), pos.0) {
 				enemies_slain.0 += 1;
 				commands.entity(entity).despawn_recursive();
 				hit_sfx = true;
 				commands
 					.spawn((SpriteSheetBundle {
 						transform: Transform::from_xyz(enemy_transform.translation.x, enemy_transform.translation.y, 125.0),
-						texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/corpse.png"), Vec2::new(8.0, 8.0), 4, 2, None, None)).clone(),
+						texture_atlas: sprite_atlases.corpse.clone(),
 						sprite: TextureAtlasSprite{
 							index: 0,
 							custom_size: Some(Vec2::new(8.0, 8.0)),
@@ -141,9 +214,78 @@ fn beam_enemy(
 					Corpse(Timer::from_seconds(CORPSE_DURATION, TimerMode::Repeating)),
 					DespawnOnExitGameState,
 				));
+				ev_w_particles.send(ParticleEvent{
+					effect: ParticleEffect::EnemyBurst,
+					position: pos.0,
+					direction: Vec2::ZERO,
+				});
+				ev_w_defeated.send(EnemyDefeated{
+					position: pos.0,
+				});
+			}
+		}
+		if hit_sfx {ev_w_sfx.send(SfxEvent::EnemyDestroyed);}
+	}
+}
+
+// Ranged enemies fire at the mech when it gets close,
+// otherwise at the nearest star in range
+fn enemy_shoot(
+	time: Res<Time>,
+	tuning: Res<GameTuning>,
+	mut commands: Commands,
+	mut shooter_query: Query<(&TruePosition, &Enemy, &mut Shooter)>,
+	star_query: Query<&Transform, With<Star>>,
+	mech_query: Query<&TruePosition, (With<Mech>, Without<Enemy>)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+) {
+	for (enemy_pos, enemy, mut shooter) in shooter_query.iter_mut() {
+		shooter.0.tick(time.delta());
+		if !shooter.0.just_finished() {continue};
+		let mech_target = mech_query.iter()
+			.map(|mech_pos| mech_pos.0)
+			.find(|mech_pos| (*mech_pos - enemy_pos.0).length() < enemy.range);
+		let star_target = star_query.iter()
+			.map(|star_transform| star_transform.translation.xy())
+			.filter(|star_pos| (*star_pos - enemy_pos.0).length() < enemy.range)
+			.min_by(|a, b| (*a - enemy_pos.0).length().total_cmp(&(*b - enemy_pos.0).length()));
+		let Some(target) = mech_target.or(star_target) else {continue};
+		spawn_enemy_bullet(&mut commands, enemy_pos.0, (target - enemy_pos.0).normalize_or_zero() * tuning.enemy_bullet_speed);
+		ev_w_sfx.send(SfxEvent::Pew);
+	}
+}
+
+// Enemy bullets damage stars, the mech blocks them with its body
+// and only takes damage from them in hardcore mode
+fn enemy_bullet_hit(
+	tuning: Res<GameTuning>,
+	mut commands: Commands,
+	bullet_query: Query<(Entity, &TruePosition, &Bullet)>,
+	mut star_query: Query<(&Transform, &mut Star)>,
+	mech_query: Query<(&TruePosition, &Mech)>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+) {
+	for (entity, bullet_pos, bullet) in bullet_query.iter() {
+		if bullet.faction != Faction::Enemy {continue};
+		let blocked = mech_query.iter().any(|(mech_pos, mech)| !mech.dashing() && (mech_pos.0 - bullet_pos.0).length() < MECH_HIT_DISTANCE);
+		let mut hit = blocked;
+		if !blocked {
+			for (star_transform, mut star) in star_query.iter_mut() {
+				if (star_transform.translation.xy() - bullet_pos.0).length() < BULLET_SMALL_HIT_RADIUS {
+					star.damage(tuning.enemy_bullet_damage);
+					hit = true;
+					break;
+				}
 			}
 		}
-		if hit_sfx {audio.play(asset_server.load("sfx/enemy_destroyed.ogg")).with_volume(SFX_VOLUME);}
+		if hit {
+			commands.entity(entity).despawn_recursive();
+			ev_w_particles.send(ParticleEvent{
+				effect: ParticleEffect::StarSpark,
+				position: bullet_pos.0,
+				direction: Vec2::ZERO,
+			});
+		}
 	}
 }
 
@@ -159,73 +301,77 @@ fn enemy_animate(
 	}
 }
 
+// Rebuild the neighbour grid from this frame's enemies
+fn update_enemy_grid(
+	mut enemy_grid: ResMut<EnemyGrid>,
+	flocking: Res<FlockingSettings>,
+	enemy_query: Query<(&TruePosition, &Velocity), With<Enemy>>,
+) {
+	enemy_grid.cell_size = flocking.separation_radius.max(flocking.alignment_radius).max(1.0);
+	enemy_grid.cells.clear();
+	for (enemy_pos, velocity) in enemy_query.iter() {
+		enemy_grid.insert(enemy_pos.0, velocity.0);
+	}
+}
+
 pub fn enemy_move(
 	mut enemy_query: Query<(&mut Transform, &mut TruePosition, &mut Velocity, &Enemy)>,
-	mut star_query: Query<(&Transform, &mut Star, Without<Enemy>)>,
-	mut mech_query: Query<(&TruePosition, &mut Mech, Without<Enemy>)>,
+	enemy_grid: Res<EnemyGrid>,
+	flocking: Res<FlockingSettings>,
+	mut star_query: Query<(Entity, &Transform, &mut Star, Without<Enemy>)>,
+	mut mech_query: Query<(&TruePosition, &mut Mech, Option<&Invulnerable>, Without<Enemy>)>,
 	retaliate: Res<Retaliate>,
-	asset_server: Res<AssetServer>,
 	time: Res<Time>,
-	audio: Res<Audio>,
+	tuning: Res<GameTuning>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_camera: EventWriter<CameraEvent>,
 ) {
-	for (mech_pos, mut mech, _) in mech_query.iter_mut() {
+	let stars: Vec<(Entity, Vec2)> = star_query.iter().map(|(entity, star_transform, _, _)| (entity, star_transform.translation.xy())).collect();
+	for (mech_pos, mut mech, invulnerable, _) in mech_query.iter_mut() {
 		for (mut enemy_transform, mut enemy_pos, mut velocity, enemy) in enemy_query.iter_mut() {
+			let weights = if retaliate.0 {tuning.enemy_retaliate_steering[enemy.spec]} else {tuning.enemy_steering[enemy.spec]};
 			let distance_to_mech = (mech_pos.0 - enemy_pos.0).length();
-			let mut direction = (mech_pos.0 - enemy_pos.0).normalize_or_zero();
-			let mut distance = 9999.0;
-
-			if !retaliate.0 {
-				if distance_to_mech < 36.0 || star_query.is_empty() {
-					if distance_to_mech < 28.0 {direction = -direction}
-					else if distance_to_mech < 36.0 {velocity.0 = direction.rotate(Vec2::from_angle(enemy.rotation * 90.0_f32.to_radians())) * velocity.0.length()};
-				} else if !star_query.is_empty() {
-					for (star_transform, mut star, _) in star_query.iter_mut() {
-						let target = star_transform.translation.xy() - enemy_pos.0;
-						let distance_metric = if enemy.spec == 1 {(star_transform.translation.xy() - enemy_pos.0).length()}
-						else {9999.0 - (star_transform.translation.xy() - mech_pos.0).length()};
-						if distance_metric < distance {
-							distance = distance_metric;
-							if target.length() < 8.0 {
-								star.health = (star.health - enemy.dps * time.delta_seconds()).clamp(0.0, 100.0);
-								direction = -target.normalize_or_zero();
-							} else {
-								direction = target.normalize_or_zero();
-							}
-						}
-					}
-				}
-			} else if retaliate.0 {
-				let mut stars_left = 0;
-				for (_,_,_) in star_query.iter() {stars_left+=1;};
-				if (distance_to_mech <= 36.0 && mech.stun_cooldown.finished()) || star_query.is_empty() {
-					if enemy.spec == 0 && distance_to_mech <= 36.0 && stars_left > 2 {
-						direction = -direction.rotate(Vec2::from_angle(enemy.rotation * 45.0_f32.to_radians()));
-					} 
-					if mech.stun_cooldown.finished() {
-						if distance_to_mech <= 8.0 {
-							mech.stun_cooldown.reset();
-							audio.play(asset_server.load("sfx/unstun.ogg")).with_volume(SFX_VOLUME);
-						}
-					}
-				} else if (distance_to_mech > 36.0 || !mech.stun_cooldown.finished()) || !star_query.is_empty() {
-					for (star_transform, mut star, _) in star_query.iter_mut() {
-						let target = star_transform.translation.xy() - enemy_pos.0;
-						let distance_metric = if enemy.spec == 1 {(star_transform.translation.xy() - enemy_pos.0).length()}
-						else {9999.0 - (star_transform.translation.xy() - mech_pos.0).length()};
-						if distance_metric < distance {
-							distance = distance_metric;
-							if target.length() < 8.0 {
-								star.health = (star.health - enemy.dps * time.delta_seconds()).clamp(0.0, 100.0);
-								direction = -target.normalize_or_zero();
-							} else {
-								direction = target.normalize_or_zero();
-							}
-						}
+			let mut direction = Vec2::ZERO;
+
+			let target = if weights.flank {steering::flank(mech_pos.0, &stars)} else {steering::nearest(enemy_pos.0, &stars)};
+			if let Some((target_star, target)) = target {
+				let distance_to_star = (target - enemy_pos.0).length();
+				if distance_to_star < enemy.range {
+					// Ranged enemies brake to a stop and let their bullets do the work
+					direction += steering::brake(velocity.0, tuning.enemy_acceleration) * weights.seek_star;
+				} else if distance_to_star < tuning.enemy_feed_distance {
+					// Feed then bounce off the star so enemies don't sit still on it
+					if let Ok((_, _, mut star, _)) = star_query.get_mut(target_star) {
+						star.damage(enemy.dps * time.delta_seconds());
 					}
+					direction -= steering::seek(enemy_pos.0, target) * weights.seek_star;
+				} else {
+					direction += steering::seek(enemy_pos.0, target) * weights.seek_star;
 				}
 			}
 
-			velocity.0 = (velocity.0 + direction * ENEMY_ACCELERATION).clamp_length(0.1, ENEMY_MAX_SPEED);
+			direction += steering::flee(enemy_pos.0, mech_pos.0, tuning.enemy_flee_radius) * weights.flee_mech;
+			direction += steering::orbit(enemy_pos.0, mech_pos.0, tuning.enemy_orbit_radius, enemy.rotation) * weights.orbit_mech;
+			// Flocking keeps groups spread out and moving together instead of piling up
+			let neighbours = enemy_grid.nearby(enemy_pos.0, flocking.separation_radius).map(|(neighbour, _)| neighbour);
+			direction += steering::separate(enemy_pos.0, neighbours, flocking.separation_radius) * weights.separate;
+			let headings = enemy_grid.nearby(enemy_pos.0, flocking.alignment_radius).map(|(_, heading)| heading);
+			direction += steering::align(headings) * weights.align;
+			// Stunned mechs are left alone, but with no stars left everything goes for the mech
+			let chase = if stars.is_empty() {1.0}
+			else if distance_to_mech <= tuning.enemy_aggro_radius && mech.stun_cooldown.finished() {weights.chase_mech}
+			else {0.0};
+			direction += steering::seek(enemy_pos.0, mech_pos.0) * chase;
+
+			if retaliate.0 && mech.stun_cooldown.finished() && mech.iframes.finished()
+			&& distance_to_mech <= MECH_HIT_DISTANCE && invulnerable.is_none() {
+				mech.stun_cooldown.reset();
+				ev_w_sfx.send(SfxEvent::Unstun);
+				ev_w_camera.send(CameraEvent::Shake(STUN_TRAUMA));
+				ev_w_camera.send(CameraEvent::HitStop(STUN_HIT_STOP));
+			}
+
+			velocity.0 = (velocity.0 + direction.clamp_length_max(1.0) * tuning.enemy_acceleration).clamp_length(0.1, tuning.enemy_max_speed);
 			enemy_pos.0 += velocity.0 * time.delta_seconds();
 			enemy_transform.translation.x = enemy_pos.0.x.round(); 
 			enemy_transform.translation.y = enemy_pos.0.y.round();
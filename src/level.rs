@@ -4,7 +4,7 @@ use std::time::Duration;
 use bevy::prelude::*;
 use bevy_kira_audio::{Audio, AudioControl, AudioTween};
 // Import components, resources, and events
-use crate::derivables::*;
+use crate::{derivables::*, boss::spawn_boss, constellation::spawn_edge, enemy::spawn_enemy, menu_widget::navigate_menus};
 
 pub struct LevelPlugin;
 
@@ -14,9 +14,12 @@ impl Plugin for LevelPlugin {
 			.add_systems(OnEnter(GameState::Level), (
 				reset_level_timer,
 				spawn_level,
+				spawn_indicators,
 			))
 			.add_systems(Update,(
 				update_level_timer,
+				spawn_incoming_enemies.after(update_level_timer),
+				update_spawn_indicators.after(spawn_incoming_enemies),
 				bullet_move,
 				star_animate,
 				constellation_lost,
@@ -26,7 +29,7 @@ impl Plugin for LevelPlugin {
 			).run_if(in_state(GameState::Level))
 			.run_if(in_state(PauseState::Unpaused)))
 			.add_systems(Update,(
-				navigate_pause.after(pause_game),
+				navigate_pause.after(pause_game).after(navigate_menus),
 			).run_if(in_state(GameState::Level))
 			.run_if(in_state(PauseState::Paused)))
 		;
@@ -36,41 +39,40 @@ impl Plugin for LevelPlugin {
 fn reset_level_timer(
 	mut no_enemies_timer: ResMut<NoEnemies>,
 	mut round_timer: ResMut<LevelInfo>,
+	mut incoming_enemies: ResMut<IncomingEnemies>,
 	mut enemies_slain: ResMut<EnemiesSlain>,
 	mut beam_charge: ResMut<BeamCharge>,
 	mut win_spawned: ResMut<WinSpawned>,
 	mut win_state: ResMut<WinState>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
 ) {
 	round_timer.round = 0;
 	round_timer.round_timer.reset();
+	round_timer.boss_spawned = false;
+	round_timer.boss_defeated = false;
+	incoming_enemies.0.clear();
 	no_enemies_timer.0.reset();
 	enemies_slain.0 = 0;
 	beam_charge.0 = 0.0;
 	win_spawned.0 = 0;
 	win_state.0 = 0;
-	audio.play(asset_server.load("sfx/unstun.ogg")).with_volume(SFX_VOLUME);
+	ev_w_sfx.send(SfxEvent::Unstun);
 }
 
 fn update_level_timer(
 	time: Res<Time>,
-	asset_server: Res<AssetServer>,
 	mech_query: Query<&TruePosition, With<Mech>>,
 	endless: Res<Endless>,
 	selected_level: Res<SelectedLevel>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	level_layout: Res<LevelLayout>,
+	sprite_atlases: Res<SpriteAtlases>,
+	tuning: Res<GameTuning>,
 	mut round_timer: ResMut<LevelInfo>,
+	mut incoming_enemies: ResMut<IncomingEnemies>,
+	mut rng: ResMut<GameRng>,
 	mut commands: Commands,
 ) {
-	let max_rounds = match selected_level.0 {
-		0 => 30,
-		1 => 45,
-		2 => 60,
-		3 => 75,
-		4 => 88,
-		_ => 999,
-	};
+	let max_rounds = level_layout.constellations[selected_level.0].rounds;
 	if round_timer.round < max_rounds || endless.0 {
 		round_timer.round_timer.tick(time.delta());
 		if round_timer.round_timer.just_finished() {
@@ -78,55 +80,142 @@ fn update_level_timer(
 			for pos in mech_query.iter() {
 				let j = (round_timer.round / 10).clamp(1, 10);
 				for _ in 0..j {
-					let r = rand::random::<f32>();
+					let r = rand::Rng::gen::<f32>(&mut rng.0);
 					let direction = if r >= 0.5 {90.0} else if r >= 0.75 {180.0} else {360.0};
-					let offset = if pos.0.length() > 1.0 {(-pos.0.normalize().rotate(Vec2::from_angle(((rand::random::<f32>() - 0.5) * direction).to_radians())) * 120.0).clamp_length(120.0, 128.0)}
-					else {(-Vec2::Y.rotate(Vec2::from_angle((rand::random::<f32>() * 360.0).to_radians())) * 120.0).clamp_length(120.0, 128.0)};
-					let spec = if rand::random::<f32>() > 0.8 {0} else {1};
-					commands
-						.spawn((SpriteSheetBundle {
-							transform: Transform::from_xyz(offset.x, offset.y, 150.0),
-							texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load(if spec == 0 {"sprites/enemy_0.png"} else {"sprites/enemy_1.png"}), Vec2::new(16.0, 16.0), 2, 2, None, None)).clone(),
-							sprite: TextureAtlasSprite{
-								index: 0,
-								custom_size: Some(Vec2::new(16.0, 16.0)),
-								..default()
-							},
-							..default()
-						},
-						Enemy{
-							spec: spec, 
-							rotation: if rand::random::<f32>() > 0.5 {1.0} else {-1.0},
-							dps: if spec == 0 {ENEMY_0_DPS} else {ENEMY_1_DPS},
-						},
-						//Velocity(Vec2::new((rand::random::<f32>() - 0.5) * ENEMY_SPEED, (rand::random::<f32>() - 0.5) * ENEMY_SPEED)),
-						Velocity(Vec2::ZERO),//(pos.0 - offset).normalize().rotate(Vec2::from_angle(80.0_f32.to_radians())) * INITIAL_TANGENTIAL_SPEED),
-						TruePosition(Vec2::new(offset.x, offset.y)),
-						AnimationTimer(Timer::from_seconds(ENEMY_ANIMATION_SPEED, TimerMode::Repeating)),
-						DespawnOnExitGameState,
-					));
+					let offset = if pos.0.length() > 1.0 {(-pos.0.normalize().rotate(Vec2::from_angle(((rand::Rng::gen::<f32>(&mut rng.0) - 0.5) * direction).to_radians())) * 120.0).clamp_length(120.0, 128.0)}
+					else {(-Vec2::Y.rotate(Vec2::from_angle((rand::Rng::gen::<f32>(&mut rng.0) * 360.0).to_radians())) * 120.0).clamp_length(120.0, 128.0)};
+					let roll = rand::Rng::gen::<f32>(&mut rng.0);
+					let spec = if roll > 0.8 {0} else if roll > 0.7 {2} else {1};
+					// Telegraph the enemy at the screen edge before it arrives
+					incoming_enemies.0.push(IncomingEnemy{
+						spec,
+						position: offset,
+						timer: Timer::from_seconds(tuning.spawn_telegraph_duration, TimerMode::Once),
+					});
 				}
 			}
 		};
 	} else {
 		round_timer.round = max_rounds;
 	}
+	// The last round of a constellation brings its boss, however it got there
+	if round_timer.round >= max_rounds && !round_timer.boss_spawned && !endless.0 {
+		round_timer.boss_spawned = true;
+		spawn_boss(&mut commands, &sprite_atlases, &tuning);
+	}
+}
+
+fn spawn_incoming_enemies(
+	time: Res<Time>,
+	sprite_atlases: Res<SpriteAtlases>,
+	tuning: Res<GameTuning>,
+	mut incoming_enemies: ResMut<IncomingEnemies>,
+	mut rng: ResMut<GameRng>,
+	mut commands: Commands,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+) {
+	for incoming in incoming_enemies.0.iter_mut() {
+		incoming.timer.tick(time.delta());
+		if incoming.timer.just_finished() {
+			let offset = incoming.position;
+			spawn_enemy(&mut commands, &sprite_atlases, &tuning, &mut rng, incoming.spec, offset);
+			// Enemies spawn off screen so flash a warning at the edge they are coming from
+			ev_w_particles.send(ParticleEvent{
+				effect: ParticleEffect::SpawnWarning,
+				position: Vec2::new(offset.x.clamp(-ORTHO_WIDTH/2.0 + 2.0, ORTHO_WIDTH/2.0 - 2.0), offset.y.clamp(-ORTHO_HEIGHT/2.0 + 2.0, ORTHO_HEIGHT/2.0 - 2.0)),
+				direction: -offset,
+			});
+		}
+	}
+	incoming_enemies.0.retain(|incoming| !incoming.timer.finished());
+}
+
+// Which of the eight edge indicators a spawn position belongs to,
+// counting anticlockwise from the right
+fn spawn_sector(position: Vec2) -> usize {
+	((position.y.atan2(position.x) / std::f32::consts::FRAC_PI_4).round() as i32).rem_euclid(8) as usize
+}
+
+fn spawn_indicators(
+	mut commands: Commands,
+	sprite_atlases: Res<SpriteAtlases>,
+) {
+	for sector in 0..8 {
+		// Push each direction out until it touches the edge of the screen
+		let direction = Vec2::from_angle(sector as f32 * std::f32::consts::FRAC_PI_4);
+		let edge = direction / (direction.x.abs() / (ORTHO_WIDTH/2.0 - 6.0)).max(direction.y.abs() / (ORTHO_HEIGHT/2.0 - 6.0));
+		let count = (-direction * 7.0).round();
+		commands
+			.spawn((SpriteSheetBundle {
+				transform: Transform::from_xyz(edge.x.round(), edge.y.round(), 300.0),
+				texture_atlas: sprite_atlases.spawn_arrow.clone(),
+				sprite: TextureAtlasSprite{
+					index: sector,
+					custom_size: Some(Vec2::new(8.0, 8.0)),
+					..default()
+				},
+				visibility: Visibility::Hidden,
+				..default()
+			},
+			SpawnIndicator(sector),
+			DespawnOnExitGameState,
+		)).with_children(|parent| {
+			parent.spawn((SpriteSheetBundle {
+				transform: Transform::from_xyz(count.x, count.y, 1.0),
+				texture_atlas: sprite_atlases.digits.clone(),
+				sprite: TextureAtlasSprite{
+					index: 0,
+					custom_size: Some(Vec2::new(4.0, 6.0)),
+					..default()
+				},
+				..default()
+			},
+			SpawnCount,
+		));
+		});
+	}
+}
+
+fn update_spawn_indicators(
+	time: Res<Time>,
+	incoming_enemies: Res<IncomingEnemies>,
+	mut indicator_query: Query<(&mut Visibility, &SpawnIndicator, &Children)>,
+	mut count_query: Query<&mut TextureAtlasSprite, With<SpawnCount>>,
+) {
+	let mut counts = [0; 8];
+	for incoming in incoming_enemies.0.iter() {
+		counts[spawn_sector(incoming.position)] += 1;
+	}
+	let blink = (time.elapsed_seconds() / SPAWN_INDICATOR_BLINK_SPEED) as usize % 2 == 0;
+	for (mut visibility, indicator, children) in indicator_query.iter_mut() {
+		let count = counts[indicator.0];
+		*visibility = if count > 0 && blink {Visibility::Visible} else {Visibility::Hidden};
+		for child in children.iter() {
+			if let Ok(mut sprite) = count_query.get_mut(*child) {
+				sprite.index = count.min(9);
+			}
+		}
+	}
 }
 
 fn spawn_level(
 	mut commands: Commands,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	mut images: ResMut<Assets<Image>>,
+	sprite_atlases: Res<SpriteAtlases>,
 	milky: Res<Milky>,
 	retaliate: Res<Retaliate>,
-	asset_server: Res<AssetServer>,
+	tuning: Res<GameTuning>,
 	level_layout: Res<LevelLayout>,
 	selected_level: Res<SelectedLevel>,
 ) {
-	let background = if milky.0 && selected_level.0 == 4 {0} else {rand::Rng::gen_range(&mut rand::thread_rng(), 1..5)};
+	let constellation = &level_layout.constellations[selected_level.0];
+	let background = if milky.0 && selected_level.0 == 4 {0}
+		else if let Some(background) = constellation.background {background}
+		else {rand::Rng::gen_range(&mut rand::thread_rng(), 1..5)};
 	commands
 		.spawn((SpriteSheetBundle {
 			transform: Transform::from_xyz(0.0, 0.0, 0.0),
-			texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/background.png"), Vec2::new(160.0, 144.0), 3, 2, None, None)).clone(),
+			texture_atlas: sprite_atlases.background.clone(),
 			sprite: TextureAtlasSprite{
 				index: background,
 				custom_size: Some(Vec2::new(160.0, 144.0)),
@@ -140,7 +229,7 @@ fn spawn_level(
 		commands
 			.spawn((SpriteSheetBundle {
 				transform: Transform::from_xyz(0.0, 60.0, 300.0),
-				texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/beam_bar.png"), Vec2::new(80.0, 20.0), 2, 4, None, None)).clone(),
+				texture_atlas: sprite_atlases.beam_bar.clone(),
 				sprite: TextureAtlasSprite{
 					index: 0,
 					custom_size: Some(Vec2::new(80.0, 20.0)),
@@ -152,11 +241,12 @@ fn spawn_level(
 			DespawnOnExitGameState,
 		));
 	}
-	for star_loc in &level_layout.constellations[selected_level.0] {
-		commands
+	let mut stars = Vec::new();
+	for star_loc in &constellation.stars {
+		let star = commands
 			.spawn((SpriteSheetBundle {
 				transform: Transform::from_xyz(star_loc.x, star_loc.y, 100.0),
-				texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/star.png"), Vec2::new(15.0, 15.0), 4, 2, None, None)).clone(),
+				texture_atlas: sprite_atlases.star.clone(),
 				sprite: TextureAtlasSprite{
 					index: 0,
 					custom_size: Some(Vec2::new(15.0, 15.0)),
@@ -164,23 +254,39 @@ fn spawn_level(
 				},
 				..default()
 			},
-			Star{
-				health: STAR_HEALTH,
-			},
+			Star::new(tuning.star_health),
 			DespawnOnExitGameState,
-		));
+		)).id();
+		stars.push((star, *star_loc));
+	}
+	for (a, b) in &constellation.edges {
+		if let (Some(from), Some(to)) = (stars.get(*a), stars.get(*b)) {
+			spawn_edge(&mut commands, &mut images, &tuning, *from, *to);
+		}
 	}
 }
 
 fn star_animate(
 	mut commands: Commands,
-	mut star_query: Query<(Entity, &mut TextureAtlasSprite, &Star)>,
+	mut star_query: Query<(Entity, &mut TextureAtlasSprite, &Transform, &Star)>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_camera: EventWriter<CameraEvent>,
 ) {
-	for (entity, mut sprite, star) in star_query.iter_mut() {
+	for (entity, mut sprite, transform, star) in star_query.iter_mut() {
 		if star.health == 0.0 {
 			commands.entity(entity).despawn_recursive();
+			ev_w_camera.send(CameraEvent::Shake(STAR_DEATH_TRAUMA));
+			ev_w_camera.send(CameraEvent::HitStop(STAR_DEATH_HIT_STOP));
 		} else {
-			sprite.index = (8.0 - (star.health/10.0)).clamp(0.0, 7.0) as usize;
+			let index = (8.0 * (1.0 - star.health / star.max_health)).clamp(0.0, 7.0) as usize;
+			if index > sprite.index {
+				ev_w_particles.send(ParticleEvent{
+					effect: ParticleEffect::StarSpark,
+					position: transform.translation.truncate(),
+					direction: Vec2::ZERO,
+				});
+			}
+			sprite.index = index;
 		}
 	}
 }
@@ -238,24 +344,19 @@ fn constellation_lost(
 fn constellation_defended(
 	round_timer: Res<LevelInfo>,
 	enemy_query: Query<With<Enemy>>,
+	incoming_enemies: Res<IncomingEnemies>,
 	endless: Res<Endless>,
 	audio: Res<Audio>,
 	time: Res<Time>,
 	selected_level: Res<SelectedLevel>,
+	level_layout: Res<LevelLayout>,
 	mut no_enemies_timer: ResMut<NoEnemies>,
 	mut win_state: ResMut<WinState>,
 	mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-	let max_rounds = match selected_level.0 {
-		0 => 30,
-		1 => 45,
-		2 => 60,
-		3 => 75,
-		4 => 88,
-		_ => 999,
-	};
-	if round_timer.round == max_rounds && !endless.0 {
-		if enemy_query.is_empty() {
+	let max_rounds = level_layout.constellations[selected_level.0].rounds;
+	if round_timer.round >= max_rounds && round_timer.boss_defeated && !endless.0 {
+		if enemy_query.is_empty() && incoming_enemies.0.is_empty() {
 			if no_enemies_timer.0.percent() == 0.0 {
 				audio.stop().fade_out(AudioTween::linear(Duration::new(2, 0)));
 			}
@@ -272,16 +373,15 @@ fn pause_game(
 	keyboard: Res<Input<KeyCode>>,
 	pause_state: Res<State<PauseState>>,
 	asset_server: Res<AssetServer>,
-	audio: Res<Audio>,
 	mut hint_text_query: Query<(&mut TextureAtlasSprite, With<HintText>)>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 	mut commands: Commands,
 	mut next_pause_state: ResMut<NextState<PauseState>>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
 ) {
 	if keyboard.just_pressed(START_BUTTON)
 	|| keyboard.just_pressed(ALT_START_BUTTON)
 	|| keyboard.just_pressed(ALT_ALT_START_BUTTON) {
-		audio.play(asset_server.load("sfx/ui_select.ogg")).with_volume(SFX_VOLUME);
+		ev_w_sfx.send(SfxEvent::UiSelect);
 		if *pause_state.get() == PauseState::Unpaused {
 			next_pause_state.set(PauseState::Paused);
 			commands
@@ -296,18 +396,14 @@ fn pause_game(
 				},
 				DespawnOnExitPauseState,
 			));
-			commands
-				.spawn((SpriteSheetBundle {
-					transform: Transform::from_xyz(0.0, 0.0, 400.0),
-					texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/pause_highlight.png"), Vec2::new(140.0, 120.0), 2, 1, None, None)).clone(),
-					sprite: TextureAtlasSprite{
-						index: 0,
-						custom_size: Some(Vec2::new(140.0, 120.0)),
-						..default()
-					},
-					..default()
-				},
-				PauseHighlight,
+			// Resume and quit buttons on the pause screen
+			commands.spawn((
+				SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, 400.0)),
+				MenuWidget::new(vec![
+					MenuItem::new(Rect::new(-35.0, -2.0, 35.0, 23.0)),
+					MenuItem::new(Rect::new(-25.0, -32.0, 25.0, -7.0)),
+				], 1, true),
+				PauseMenu,
 				DespawnOnExitPauseState,
 			));
 			for (mut sprite, _) in hint_text_query.iter_mut() {
@@ -328,59 +424,33 @@ fn pause_game(
 
 fn navigate_pause(
 	keyboard: Res<Input<KeyCode>>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
+	menu_query: Query<(), With<PauseMenu>>,
 	mut hint_text_query: Query<(&mut TextureAtlasSprite, With<HintText>)>,
-	mut pause_highlight_query: Query<(&mut TextureAtlasSprite, (With<PauseHighlight>, Without<HintText>))>,
 	mut next_pause_state: ResMut<NextState<PauseState>>,
 	mut next_game_state: ResMut<NextState<GameState>>,
+	mut ev_r_menu: EventReader<MenuEvent>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
 ) {
-	if keyboard.just_pressed(UP_BUTTON)
-	|| keyboard.just_pressed(ALT_UP_BUTTON)
-	|| keyboard.just_pressed(DOWN_BUTTON)
-	|| keyboard.just_pressed(ALT_DOWN_BUTTON)
-	|| keyboard.just_pressed(A_BUTTON) 
-	|| keyboard.just_pressed(ALT_A_BUTTON) 
-	|| keyboard.just_pressed(B_BUTTON)
-	|| keyboard.just_pressed(ALT_B_BUTTON)
-	|| keyboard.just_pressed(START_BUTTON)
-	|| keyboard.just_pressed(ALT_START_BUTTON)
-	|| keyboard.just_pressed(ALT_ALT_START_BUTTON) {
-		audio.play(asset_server.load("sfx/ui_select.ogg")).with_volume(SFX_VOLUME);
+	let mut unpause = false;
+	for ev in ev_r_menu.iter().filter(|ev| menu_query.contains(ev.menu)) {
+		// Quitting goes back to the title screen, anything else resumes
+		if ev.action == MenuAction::Confirm(1) {
+			next_game_state.set(GameState::Menu);
+		}
+		unpause = true;
 	}
 
-	if keyboard.just_pressed(UP_BUTTON)
-	|| keyboard.just_pressed(ALT_UP_BUTTON)
-	|| keyboard.just_pressed(DOWN_BUTTON)
-	|| keyboard.just_pressed(ALT_DOWN_BUTTON) {
-		for (mut sprite, _) in pause_highlight_query.iter_mut() {
-			sprite.index = (sprite.index + 1) % 2;
-		}
-	} else if keyboard.just_pressed(A_BUTTON)
-	|| keyboard.just_pressed(ALT_A_BUTTON) {
-		for (mut sprite, _) in hint_text_query.iter_mut() {
-			sprite.index = 0;
-		}
-		for (sprite, _) in pause_highlight_query.iter() {
-			if sprite.index == 1 {
-				next_pause_state.set(PauseState::Unpaused);
-				next_game_state.set(GameState::Menu);
-			} else {
-				next_pause_state.set(PauseState::Unpaused);
-			}
-		}
-	} else if keyboard.just_pressed(B_BUTTON)
-	|| keyboard.just_pressed(ALT_B_BUTTON) {
-		for (mut sprite, _) in hint_text_query.iter_mut() {
-			sprite.index = 0;
-		}
-		next_pause_state.set(PauseState::Unpaused);
-	} else if keyboard.just_pressed(START_BUTTON)
+	if keyboard.just_pressed(START_BUTTON)
 	|| keyboard.just_pressed(ALT_START_BUTTON)
 	|| keyboard.just_pressed(ALT_ALT_START_BUTTON) {
+		ev_w_sfx.send(SfxEvent::UiSelect);
+		unpause = true;
+	}
+
+	if unpause {
 		next_pause_state.set(PauseState::Unpaused);
 		for (mut sprite, _) in hint_text_query.iter_mut() {
 			sprite.index = 0;
 		}
 	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,89 @@
+// Import Bevy game engine essentials
+use bevy::{prelude::*, asset::{AssetLoader, LoadContext, LoadedAsset, BoxedFuture}};
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for loading the constellation layouts from the level
+// file, which the level editor saves back to
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_asset::<LevelLayout>()
+			.init_asset_loader::<LevelLayoutLoader>()
+			.add_systems(Startup, (
+				load_levels,
+			))
+			.add_systems(Update, (
+				apply_levels,
+			))
+		;
+	}
+}
+
+#[derive(Default)]
+struct LevelLayoutLoader;
+
+impl AssetLoader for LevelLayoutLoader {
+	fn load<'a>(
+		&'a self,
+		bytes: &'a [u8],
+		load_context: &'a mut LoadContext,
+	) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+		Box::pin(async move {
+			let layout: LevelLayout = ron::de::from_bytes(bytes)?;
+			load_context.set_default_asset(LoadedAsset::new(layout));
+			Ok(())
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["levels.ron"]
+	}
+}
+
+fn load_levels(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+) {
+	commands.insert_resource(LevelLayoutHandle(asset_server.load(LEVEL_FILE)));
+}
+
+// Copies the file into the resource whenever it loads or changes,
+// keeping the random and endless constellations the menu generated.
+// The file can be edited by hand so it's fitted to the level slots,
+// any it's missing keep what they had before
+fn apply_levels(
+	mut ev_r_levels: EventReader<AssetEvent<LevelLayout>>,
+	layout_assets: Res<Assets<LevelLayout>>,
+	layout_handle: Res<LevelLayoutHandle>,
+	mut level_layout: ResMut<LevelLayout>,
+	mut editor: ResMut<LevelEditor>,
+) {
+	for event in ev_r_levels.iter() {
+		match event {
+			AssetEvent::Created { handle } | AssetEvent::Modified { handle } if *handle == layout_handle.0 => {
+				let Some(loaded) = layout_assets.get(handle) else {continue};
+				if loaded.constellations.len() != LEVEL_SLOTS {
+					warn!("{} has {} constellations, expected {}", LEVEL_FILE, loaded.constellations.len(), LEVEL_SLOTS);
+				}
+				let generated: Vec<Constellation> = level_layout.constellations.iter().skip(EDITOR_LEVELS).cloned().collect();
+				let mut constellations = loaded.constellations.clone();
+				constellations.truncate(LEVEL_SLOTS);
+				constellations.extend(level_layout.constellations.iter().skip(constellations.len()).cloned());
+				level_layout.constellations = constellations;
+				// The editor's star indices may not exist in the new file
+				editor.grabbed = None;
+				editor.linking = None;
+				for (constellation, generated) in level_layout.constellations.iter_mut().skip(EDITOR_LEVELS).zip(generated) {
+					if constellation.stars.is_empty() {
+						constellation.stars = generated.stars;
+						constellation.edges = generated.edges;
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+}
@@ -1,5 +1,5 @@
 // Import Bevy game engine essentials
-use bevy::prelude::*;
+use bevy::{prelude::*, asset::LoadState};
 // Import components, resources, and events
 use crate::derivables::*;
 
@@ -8,6 +8,9 @@ pub struct LoadingPlugin;
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
         app
+			.add_systems(Startup, 
+				load_game_assets,
+			)
 			.add_systems(OnEnter(GameState::Loading), 
 				spawn_loading_screen,
 			)
@@ -19,13 +22,24 @@ impl Plugin for LoadingPlugin {
 	}
 }
 
+// Start loading everything the game needs as soon as it boots,
+// holding the handles so assets stay loaded between levels
+fn load_game_assets(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+) {
+	commands.insert_resource(GameAssets{
+		images: IMAGE_ASSETS.iter().map(|path| asset_server.load(*path)).collect(),
+		bgm: BGM_ASSETS.iter().map(|path| asset_server.load(*path)).collect(),
+	});
+}
+
 fn spawn_loading_screen(
 	asset_server: Res<AssetServer>,
 	mut commands: Commands,
-	mut loading_length: ResMut<LoadingLength>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	mut loading_progress: ResMut<LoadingProgress>,
+	sprite_atlases: Res<SpriteAtlases>,
 	mut hint_text_query: Query<(&mut TextureAtlasSprite, With<HintText>)>,
-	mut loading_timer: ResMut<LoadingTimer>,
 ) {
 	commands
 		.spawn((SpriteBundle {
@@ -43,7 +57,7 @@ fn spawn_loading_screen(
 	commands
 		.spawn((SpriteSheetBundle {
 			transform: Transform::from_xyz(0.0, -10.0, 200.0),
-			texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("text/loading.png"), Vec2::new(160.0, 144.0), 5, 1, None, None)).clone(),
+			texture_atlas: sprite_atlases.loading_text.clone(),
 			sprite: TextureAtlasSprite{
 				index: 0,
 				custom_size: Some(Vec2::new(160.0, 144.0)),
@@ -55,8 +69,43 @@ fn spawn_loading_screen(
 		DespawnOnExitGameState,
 	));
 
-	loading_length.current = 0;
-	loading_length.total = rand::Rng::gen_range(&mut rand::thread_rng(), 1..TOTAL_LOAD);
+	loading_progress.loaded = 0;
+	loading_progress.total = 0;
+	loading_progress.failed = 0;
+
+	// Hidden until something fails to load
+	commands
+		.spawn((SpriteBundle {
+			transform: Transform::from_xyz(0.0, -64.0, 450.0),
+			sprite: Sprite {
+				color: Color::BLACK,
+				custom_size: Some(Vec2::new(ORTHO_WIDTH, 10.0)),
+				..default()
+			},
+			visibility: Visibility::Hidden,
+			..default()
+		},
+		LoadingError,
+		DespawnOnExitGameState,
+	));
+	let message = LOADING_ERROR_TEXT;
+	for (column, character) in message.chars().enumerate() {
+		commands
+			.spawn((SpriteSheetBundle {
+				transform: Transform::from_xyz(-2.0 * message.len() as f32 + 2.0 + 4.0 * column as f32, -64.0, 460.0),
+				texture_atlas: sprite_atlases.font.clone(),
+				sprite: TextureAtlasSprite{
+					index: FONT_CHARS.find(character).unwrap_or(0),
+					custom_size: Some(Vec2::new(4.0, 6.0)),
+					..default()
+				},
+				visibility: Visibility::Hidden,
+				..default()
+			},
+			LoadingError,
+			DespawnOnExitGameState,
+		));
+	}
 
 	for (mut sprite, _) in hint_text_query.iter_mut() {
 		let luck = rand::random::<f32>();
@@ -70,32 +119,71 @@ fn spawn_loading_screen(
 			25
 		};
 	}
-
-	loading_timer.0.reset();
 }
 
+// Loading text fills up as assets finish loading, and the
+// level can only be started once every asset is ready
 fn advance_loading_screen(
-	time: Res<Time>,
 	keyboard: Res<Input<KeyCode>>,
-	mut loading_length: ResMut<LoadingLength>,
-	mut loading_timer: ResMut<LoadingTimer>,
+	asset_server: Res<AssetServer>,
+	game_assets: Res<GameAssets>,
+	sfx_handles: Res<SfxHandles>,
+	layout_handle: Res<LevelLayoutHandle>,
+	tuning_handle: Res<TuningHandle>,
+	catalogue_handle: Res<StarCatalogueHandle>,
+	mut loading_progress: ResMut<LoadingProgress>,
 	mut loading_text_query: Query<(&mut TextureAtlasSprite, With<LoadingText>)>,
 	mut hint_text_query: Query<(&mut TextureAtlasSprite, (With<HintText>, Without<LoadingText>))>,
+	mut error_query: Query<&mut Visibility, With<LoadingError>>,
 	mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-	loading_timer.0.tick(time.delta());
-	if loading_timer.0.just_finished() {
-		loading_length.current += 1;
-		for (mut sprite, _) in loading_text_query.iter_mut() {
-			if loading_length.current < loading_length.total {
-				sprite.index = (sprite.index + 1) % 4;
-			} else {
-				sprite.index = 4;
-			}
+	let handle_ids = game_assets.images.iter().map(|handle| handle.id())
+		.chain(game_assets.bgm.iter().map(|handle| handle.id()))
+		.chain(sfx_handles.sounds.values().map(|handle| handle.id()))
+		.chain([layout_handle.0.id(), tuning_handle.0.id(), catalogue_handle.0.id()]);
+
+	// A missing file isn't counted as loaded, the player is told
+	// and can choose to play without it or go back to the title
+	let mut failed = Vec::new();
+	loading_progress.total = 0;
+	loading_progress.loaded = 0;
+	for handle_id in handle_ids {
+		loading_progress.total += 1;
+		match asset_server.get_load_state(handle_id) {
+			LoadState::Loaded => loading_progress.loaded += 1,
+			LoadState::Failed => failed.push(handle_id),
+			_ => (),
+		}
+	}
+	// Only log each failure once instead of every frame
+	if failed.len() > loading_progress.failed {
+		for handle_id in failed.iter() {
+			let path = asset_server.get_handle_path(*handle_id);
+			error!("failed to load asset {:?}", path.as_ref().map(|path| path.path()));
+		}
+		loading_progress.failed = failed.len();
+		for mut visibility in error_query.iter_mut() {
+			*visibility = Visibility::Visible;
+		}
+	}
+	let loaded = loading_progress.loaded >= loading_progress.total;
+	let settled = loading_progress.loaded + loading_progress.failed >= loading_progress.total;
+
+	for (mut sprite, _) in loading_text_query.iter_mut() {
+		if loaded {
+			sprite.index = 4;
+		} else {
+			sprite.index = (4 * loading_progress.loaded / loading_progress.total.max(1)).clamp(0, 3);
 		}
 	}
 
-	if loading_length.current >= loading_length.total && (keyboard.just_pressed(A_BUTTON)
+	if loading_progress.failed > 0 && (keyboard.just_pressed(B_BUTTON)
+	|| keyboard.just_pressed(ALT_B_BUTTON)) {
+		next_game_state.set(GameState::Menu);
+		for (mut sprite, _) in hint_text_query.iter_mut() {
+			sprite.index = 0;
+		}
+	} else if settled && (keyboard.just_pressed(A_BUTTON)
 	|| keyboard.just_pressed(ALT_A_BUTTON)
 	|| keyboard.just_pressed(START_BUTTON)
 	|| keyboard.just_pressed(ALT_START_BUTTON)
@@ -6,14 +6,27 @@ use bevy::{prelude::*, window::WindowResolution};
 
 // MODULES
 mod audio;
+mod boss;
+mod catalogue;
+mod constellation;
+mod editor;
 mod enemy;
 mod level;
+mod levels;
 mod loading;
 mod mech;
 mod menu;
+mod menu_widget;
+mod particles;
+mod pickup;
 mod post_processing;
+mod screen_effects;
+mod secret;
 mod select;
 mod setup;
+mod star;
+mod steering;
+mod tuning;
 mod win;
 
 mod derivables;
@@ -51,6 +64,12 @@ fn main() {
 			})
 			// Prevents pixel art sprites from becoming blurry
 			.set(ImagePlugin::default_nearest())
+			// Reload changed assets such as the balance file while playing
+			.set(AssetPlugin {
+				#[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+				watch_for_changes: bevy::asset::ChangeWatcher::with_delay(std::time::Duration::from_millis(200)),
+				..default()
+			})
 		)
 
 		// Plugins
@@ -58,16 +77,31 @@ fn main() {
 			audio::AudioPlugin,
 			// Kira audio plugin for Bevy for playing sound files
 			bevy_kira_audio::AudioPlugin,
-			enemy::EnemyPlugin,
-			level::LevelPlugin,
+			catalogue::CataloguePlugin,
+			editor::EditorPlugin,
+			levels::LevelsPlugin,
 			loading::LoadingPlugin,
-			mech::MechPlugin,
 			menu::MenuPlugin,
+			menu_widget::MenuWidgetPlugin,
 			post_processing::PostProcessingPlugin,
+			secret::SecretPlugin,
 			select::SelectPlugin,
 			setup::SetupPlugin,
+			tuning::TuningPlugin,
 			win::WinPlugin,
 		))
+		// Gameplay plugins, kept separate since plugin tuples max out at 15
+		.add_plugins((
+			boss::BossPlugin,
+			constellation::ConstellationPlugin,
+			enemy::EnemyPlugin,
+			level::LevelPlugin,
+			mech::MechPlugin,
+			particles::ParticlePlugin,
+			pickup::PickupPlugin,
+			screen_effects::ScreenEffectsPlugin,
+			star::StarPlugin,
+		))
 		;
 
 	{
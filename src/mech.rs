@@ -1,6 +1,5 @@
 // Import Bevy game engine essentials
 use bevy::{prelude::*, math::Vec3Swizzles};
-use bevy_kira_audio::{Audio, AudioControl};
 // Import components, resources, and events
 use crate::{derivables::*, enemy::enemy_move};
 
@@ -18,6 +17,10 @@ impl Plugin for MechPlugin {
 				mech_shoot,
 				mech_beam,
 				mech_stun.after(enemy_move),
+				mech_contact_damage.after(enemy_move),
+				reflect_bullets,
+				mech_iframes.after(mech_contact_damage),
+				health_pips_animate,
 				mech_animate,
 				slash_animate,
 				beam_animate,
@@ -25,7 +28,7 @@ impl Plugin for MechPlugin {
 			).run_if(in_state(GameState::Level))
 			.run_if(in_state(PauseState::Unpaused)))
 			.add_systems( Update, (
-				disable_retaliate,
+				toggle_retaliate,
 				hide_retaliation_text
 			))
 		;
@@ -34,13 +37,14 @@ impl Plugin for MechPlugin {
 
 fn spawn_mech(
 	mut commands: Commands,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-	asset_server: Res<AssetServer>,
+	sprite_atlases: Res<SpriteAtlases>,
+	tuning: Res<GameTuning>,
+	hardcore: Res<Hardcore>,
 ) {
 	commands
 		.spawn((SpriteSheetBundle {
 			transform: Transform::from_xyz(0.0, 0.0, 200.0),
-			texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/mech.png"), Vec2::new(16.0, 16.0), 4, 4, None, None)).clone(),
+			texture_atlas: sprite_atlases.mech.clone(),
 			sprite: TextureAtlasSprite{
 				index: 0,
 				custom_size: Some(Vec2::new(16.0, 16.0)),
@@ -50,10 +54,17 @@ fn spawn_mech(
 		},
 		Velocity(Vec2::ZERO),
 		Mech{
-			stun_cooldown: Timer::from_seconds(STUN_COOLDOWN, TimerMode::Once),
-			slash_cooldown: Timer::from_seconds(SLASH_COOLDOWN, TimerMode::Once),
-			shoot_cooldown: Timer::from_seconds(SHOOT_COOLDOWN, TimerMode::Once),
-			beam_cooldown: Timer::from_seconds(BEAM_COOLDOWN, TimerMode::Once),
+			stun_cooldown: Timer::from_seconds(tuning.stun_cooldown, TimerMode::Once),
+			slash_cooldown: Timer::from_seconds(tuning.slash_cooldown, TimerMode::Once),
+			shoot_cooldown: Timer::from_seconds(tuning.shoot_cooldown, TimerMode::Once),
+			beam_cooldown: Timer::from_seconds(tuning.beam_cooldown, TimerMode::Once),
+			health: MECH_HEALTH,
+			iframes: Timer::from_seconds(0.0, TimerMode::Once),
+			dash: Timer::from_seconds(0.0, TimerMode::Once),
+			dash_cooldown: Timer::from_seconds(tuning.dash_cooldown, TimerMode::Once),
+			dash_trail: Timer::from_seconds(MECH_DASH_TRAIL_RATE, TimerMode::Repeating),
+			double_tap: Timer::from_seconds(0.0, TimerMode::Once),
+			last_tap: None,
 		},
 		TruePosition(Vec2::new(0.0, 0.0)),
 		Direction::Forward,
@@ -63,7 +74,7 @@ fn spawn_mech(
 		parent
 			.spawn((SpriteSheetBundle {
 				transform: Transform::from_xyz(0.0, -16.0, 10.0),
-				texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/forward_slash.png"), Vec2::new(24.0, 16.0), 4, 2, None, None)).clone(),
+				texture_atlas: sprite_atlases.slash(Direction::Forward),
 				sprite: TextureAtlasSprite{
 					index: 0,
 					custom_size: Some(Vec2::new(24.0, 16.0)),
@@ -81,7 +92,7 @@ fn spawn_mech(
 		parent
 			.spawn((SpriteSheetBundle {
 				transform: Transform::from_xyz(0.0, 16.0, 10.0),
-				texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/backward_slash.png"), Vec2::new(24.0, 16.0), 4, 2, None, None)).clone(),
+				texture_atlas: sprite_atlases.slash(Direction::Backward),
 				sprite: TextureAtlasSprite{
 					index: 0,
 					custom_size: Some(Vec2::new(24.0, 16.0)),
@@ -99,7 +110,7 @@ fn spawn_mech(
 		parent
 			.spawn((SpriteSheetBundle {
 				transform: Transform::from_xyz(-16.0, 0.0, 10.0),
-				texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/left_slash.png"), Vec2::new(16.0, 24.0), 4, 2, None, None)).clone(),
+				texture_atlas: sprite_atlases.slash(Direction::Left),
 				sprite: TextureAtlasSprite{
 					index: 0,
 					custom_size: Some(Vec2::new(16.0, 24.0)),
@@ -117,7 +128,7 @@ fn spawn_mech(
 		parent
 			.spawn((SpriteSheetBundle {
 				transform: Transform::from_xyz(16.0, 0.0, 10.0),
-				texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/right_slash.png"), Vec2::new(16.0, 24.0), 4, 2, None, None)).clone(),
+				texture_atlas: sprite_atlases.slash(Direction::Right),
 				sprite: TextureAtlasSprite{
 					index: 0,
 					custom_size: Some(Vec2::new(16.0, 24.0)),
@@ -133,6 +144,23 @@ fn spawn_mech(
 		));
 	})
 	;
+	if hardcore.0 {
+		for i in 0..MECH_HEALTH {
+			commands
+				.spawn((SpriteBundle {
+					transform: Transform::from_xyz(74.0 - 5.0 * (MECH_HEALTH - 1 - i) as f32, -66.0, 300.0),
+					sprite: Sprite {
+						color: COLOR_D,
+						custom_size: Some(Vec2::new(3.0, 3.0)),
+						..default()
+					},
+					..default()
+				},
+				HealthPip(i),
+				DespawnOnExitGameState,
+			));
+		}
+	}
 }
 
 fn mech_animate(
@@ -192,86 +220,142 @@ fn beam_animate(
 fn mech_move(
 	time: Res<Time>,
 	keyboard: Res<Input<KeyCode>>,
-	asset_server: Res<AssetServer>,
+	eight_way: Res<EightWay>,
+	drift: Res<Drift>,
+	tuning: Res<GameTuning>,
 	mut commands: Commands,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	sprite_atlases: Res<SpriteAtlases>,
 	mut dust_timer: ResMut<DustTimer>,
-	mut mech_query: Query<(&mut Transform, &mut TruePosition, &mut Direction, &mut Velocity, &Mech)>,
+	mut mech_query: Query<(&mut Transform, &mut TruePosition, &mut Direction, &mut Velocity, &mut Mech, Option<&SpeedBoost>)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
 ) {
-	for (mut transform, mut pos, mut direction, mut velocity, mech) in mech_query.iter_mut() {
-		let mut moving = false;
-		if keyboard.pressed(UP_BUTTON)
-		|| keyboard.pressed(ALT_UP_BUTTON) {
-			moving = true;
-			*direction = Direction::Backward;
-		} else if keyboard.pressed(DOWN_BUTTON)
-		|| keyboard.pressed(ALT_DOWN_BUTTON) {
-			moving = true;
-			*direction = Direction::Forward;
-		} else if keyboard.pressed(LEFT_BUTTON)
-		|| keyboard.pressed(ALT_LEFT_BUTTON) {
-			moving = true;
-			*direction = Direction::Left;
-		} else if keyboard.pressed(RIGHT_BUTTON)
-		|| keyboard.pressed(ALT_RIGHT_BUTTON) {
-			moving = true;
-			*direction = Direction::Right;
+	for (mut transform, mut pos, mut direction, mut velocity, mut mech, speed_boost) in mech_query.iter_mut() {
+		let up = keyboard.pressed(UP_BUTTON) || keyboard.pressed(ALT_UP_BUTTON);
+		let down = keyboard.pressed(DOWN_BUTTON) || keyboard.pressed(ALT_DOWN_BUTTON);
+		let left = keyboard.pressed(LEFT_BUTTON) || keyboard.pressed(ALT_LEFT_BUTTON);
+		let right = keyboard.pressed(RIGHT_BUTTON) || keyboard.pressed(ALT_RIGHT_BUTTON);
+		let input = if eight_way.0 {
+			let mut input = Vec2::ZERO;
+			if up {input.y += 1.0};
+			if down {input.y -= 1.0};
+			if left {input.x -= 1.0};
+			if right {input.x += 1.0};
+			input
+		} else if up {
+			Vec2::Y
+		} else if down {
+			Vec2::NEG_Y
+		} else if left {
+			Vec2::NEG_X
+		} else if right {
+			Vec2::X
+		} else {
+			Vec2::ZERO
+		};
+		let moving = input != Vec2::ZERO;
+		if moving {
+			*direction = Direction::nearest(input, *direction);
 		}
-		if mech.stun_cooldown.finished() && mech.beam_cooldown.finished() {
-			let slowdown = if !mech.slash_cooldown.finished() || !mech.shoot_cooldown.finished() {0.4} else {1.0};
-			if keyboard.pressed(UP_BUTTON)
-			|| keyboard.pressed(ALT_UP_BUTTON) {
-				pos.0.y += MECH_SPEED * slowdown * time.delta_seconds();
-				//velocity.0.y = (velocity.0.y + MECH_ACCELERATION * slowdown).clamp(-MAX_MECH_SPEED, MAX_MECH_SPEED);
-			} else if keyboard.pressed(DOWN_BUTTON)
-			|| keyboard.pressed(ALT_DOWN_BUTTON) {
-				pos.0.y -= MECH_SPEED * slowdown * time.delta_seconds();
-				//velocity.0.y = (velocity.0.y - MECH_ACCELERATION * slowdown).clamp(-MAX_MECH_SPEED, MAX_MECH_SPEED);
-			} else if keyboard.pressed(LEFT_BUTTON)
-			|| keyboard.pressed(ALT_LEFT_BUTTON) {
-				pos.0.x -= MECH_SPEED * slowdown * time.delta_seconds();
-				//velocity.0.x = (velocity.0.x - MECH_ACCELERATION * slowdown).clamp(-MAX_MECH_SPEED, MAX_MECH_SPEED);
-			} else if keyboard.pressed(RIGHT_BUTTON)
-			|| keyboard.pressed(ALT_RIGHT_BUTTON) {
-				pos.0.x += MECH_SPEED * slowdown * time.delta_seconds();
-				//velocity.0.x = (velocity.0.x + MECH_ACCELERATION * slowdown).clamp(-MAX_MECH_SPEED, MAX_MECH_SPEED);
+		let able = mech.stun_cooldown.finished() && mech.beam_cooldown.finished();
+
+		// Tapping the same direction twice in quick succession dashes
+		mech.dash.tick(time.delta());
+		mech.dash_cooldown.tick(time.delta());
+		mech.double_tap.tick(time.delta());
+		let tapped = if keyboard.just_pressed(UP_BUTTON) || keyboard.just_pressed(ALT_UP_BUTTON) {Some(Direction::Backward)}
+		else if keyboard.just_pressed(DOWN_BUTTON) || keyboard.just_pressed(ALT_DOWN_BUTTON) {Some(Direction::Forward)}
+		else if keyboard.just_pressed(LEFT_BUTTON) || keyboard.just_pressed(ALT_LEFT_BUTTON) {Some(Direction::Left)}
+		else if keyboard.just_pressed(RIGHT_BUTTON) || keyboard.just_pressed(ALT_RIGHT_BUTTON) {Some(Direction::Right)}
+		else {None};
+		if let Some(tapped) = tapped {
+			if mech.last_tap == Some(tapped) && !mech.double_tap.finished() && mech.dash_cooldown.finished() && able {
+				mech.dash = Timer::from_seconds(MECH_DASH_DURATION, TimerMode::Once);
+				mech.dash_cooldown.reset();
+				mech.dash_trail.reset();
+				// Don't cut short the longer i-frames from being hit
+				if mech.iframes.remaining_secs() < MECH_DASH_IFRAMES {
+					mech.iframes = Timer::from_seconds(MECH_DASH_IFRAMES, TimerMode::Once);
+				}
+				mech.last_tap = None;
+				velocity.0 = if moving {input.normalize()} else {direction.vector()} * tuning.dash_speed;
+				ev_w_sfx.send(SfxEvent::Slash);
+			} else {
+				mech.double_tap = Timer::from_seconds(MECH_DOUBLE_TAP_WINDOW, TimerMode::Once);
+				mech.last_tap = Some(tapped);
+			}
+		}
+
+		let mut multiplier = if !mech.slash_cooldown.finished() || !mech.shoot_cooldown.finished() {0.4} else {1.0};
+		if speed_boost.is_some() {multiplier *= tuning.speed_boost_multiplier};
+		if mech.dashing() {
+			// Dashes keep their speed and heading until they end
+			velocity.0 = velocity.0.normalize_or_zero() * tuning.dash_speed;
+		} else if drift.0 {
+			// Accelerate towards top speed and slide to a stop once let go
+			if able && moving {
+				velocity.0 += input.normalize() * tuning.mech_acceleration * multiplier * time.delta_seconds();
+			} else {
+				velocity.0 *= (1.0 - tuning.mech_damping * time.delta_seconds()).max(0.0);
+				if velocity.0.length() < 1.0 {velocity.0 = Vec2::ZERO};
 			}
+			velocity.0 = velocity.0.clamp_length_max(tuning.max_mech_speed * multiplier);
+		} else if able {
+			// Diagonals are normalised so they aren't faster than straight lines
+			velocity.0 = input.normalize_or_zero() * tuning.mech_speed * multiplier;
+		} else {
+			velocity.0 = Vec2::ZERO;
 		}
-		//if velocity.0.length() < 10.0 {velocity.0 = Vec2::ZERO} else {velocity.0 = velocity.0 * MECH_DAMPING};
-		//pos.0 += velocity.0 * time.delta_seconds();
+		pos.0 += velocity.0 * time.delta_seconds();
+		// Stop sliding into the edges of the screen
+		if pos.0.x.abs() > 72.0 {velocity.0.x = 0.0};
+		if pos.0.y.abs() > 64.0 {velocity.0.y = 0.0};
 		pos.0 = Vec2::new(pos.0.x.clamp(-72.0, 72.0), pos.0.y.clamp(-64.0, 64.0));
 		transform.translation.x = pos.0.x.round(); 
 		transform.translation.y = pos.0.y.round();
 
+		// Dashing leaves a trail of after-images behind
+		if mech.dashing() {
+			mech.dash_trail.tick(time.delta());
+			if mech.dash_trail.just_finished() {
+				spawn_dust(&mut commands, &sprite_atlases, transform.translation.xy(), *direction, Vec2::ZERO);
+			}
+		}
+
 		dust_timer.0.tick(time.delta());
 		if dust_timer.0.just_finished() && moving == true {
-			commands
-				.spawn((SpriteSheetBundle {
-					transform: Transform::from_xyz(transform.translation.x, transform.translation.y, 190.0),
-					texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/dust.png"), Vec2::new(8.0, 8.0), 2, 2, None, None)).clone(),
-					sprite: TextureAtlasSprite{
-						flip_x: if *direction == Direction::Left {true} else {false},
-						flip_y: if *direction == Direction::Forward {true} else {false},
-						index: 0,
-						custom_size: Some(Vec2::new(8.0, 8.0)),
-						..default()
-					},
-					..default()
-				},
-				TruePosition(transform.translation.xy()),
-				Dust(Timer::from_seconds(DUST_DURATION, TimerMode::Repeating)),
-				Velocity(match *direction {
-					Direction::Forward => Vec2::new(0.0, DUST_SPEED),
-					Direction::Backward => Vec2::new(0.0, -DUST_SPEED),
-					Direction::Left => Vec2::new(DUST_SPEED, 0.0),
-					Direction::Right => Vec2::new(-DUST_SPEED, 0.0),
-				}),
-				DespawnOnExitGameState,
-			));
+			spawn_dust(&mut commands, &sprite_atlases, transform.translation.xy(), *direction, -input.normalize_or_zero() * DUST_SPEED);
 		}
 	}
 }
 
+// Dust puffs from walking and the after-images left by dashing
+fn spawn_dust(
+	commands: &mut Commands,
+	sprite_atlases: &SpriteAtlases,
+	position: Vec2,
+	direction: Direction,
+	velocity: Vec2,
+) {
+	commands
+		.spawn((SpriteSheetBundle {
+			transform: Transform::from_xyz(position.x, position.y, 190.0),
+			texture_atlas: sprite_atlases.dust.clone(),
+			sprite: TextureAtlasSprite{
+				flip_x: if direction == Direction::Left {true} else {false},
+				flip_y: if direction == Direction::Forward {true} else {false},
+				index: 0,
+				custom_size: Some(Vec2::new(8.0, 8.0)),
+				..default()
+			},
+			..default()
+		},
+		TruePosition(position),
+		Dust(Timer::from_seconds(DUST_DURATION, TimerMode::Repeating)),
+		Velocity(velocity),
+		DespawnOnExitGameState,
+	));
+}
+
 fn dust_animate(
 	mut commands: Commands,
 	mut dust_query: Query<(Entity, &mut TruePosition, &mut Transform, &mut TextureAtlasSprite, &mut Dust, &Velocity)>,
@@ -297,18 +381,19 @@ fn mech_stun(
 	mut slash_query: Query<(&mut Slash, Without<Mech>)>,
 	mut enemies_slain: ResMut<EnemiesSlain>,
 	mut beam_charge: ResMut<BeamCharge>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	sprite_atlases: Res<SpriteAtlases>,
 	retaliate: Res<Retaliate>,
 	enemy_query: Query<(Entity, &Transform, &TruePosition, With<Enemy>)>,
 	time: Res<Time>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_defeated: EventWriter<EnemyDefeated>,
 ) {
 	for (mech_pos, mut mech) in mech_query.iter_mut() {
 		mech.stun_cooldown.tick(time.delta());
 		mech.beam_cooldown.tick(time.delta());
 		if mech.stun_cooldown.just_finished() && retaliate.0 {
-			audio.play(asset_server.load("sfx/slash.ogg")).with_volume(SFX_VOLUME);
+			ev_w_sfx.send(SfxEvent::Slash);
 			let mut hit_sfx = false;
 			for (mut slash, _) in slash_query.iter_mut() {
 				slash.active = true;
@@ -316,13 +401,13 @@ fn mech_stun(
 			for (entity, transform, enemy_pos, _) in enemy_query.iter() {
 				if (mech_pos.0 - enemy_pos.0).length() < 16.0 {
 					enemies_slain.0 += 1;
-					beam_charge.0 += 1.0;
+					beam_charge.add(1.0);
 					commands.entity(entity).despawn_recursive();
 					hit_sfx = true;
 					commands
 						.spawn((SpriteSheetBundle {
 							transform: Transform::from_xyz(transform.translation.x, transform.translation.y, 125.0),
-							texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/corpse.png"), Vec2::new(8.0, 8.0), 4, 2, None, None)).clone(),
+							texture_atlas: sprite_atlases.corpse.clone(),
 							sprite: TextureAtlasSprite{
 								index: 0,
 								custom_size: Some(Vec2::new(8.0, 8.0)),
@@ -333,9 +418,116 @@ fn mech_stun(
 						Corpse(Timer::from_seconds(CORPSE_DURATION, TimerMode::Repeating)),
 						DespawnOnExitGameState,
 					));
+					ev_w_particles.send(ParticleEvent{
+						effect: ParticleEffect::EnemyBurst,
+						position: enemy_pos.0,
+						direction: Vec2::ZERO,
+					});
+					ev_w_defeated.send(EnemyDefeated{
+						position: enemy_pos.0,
+					});
 				}
 			}
-			if hit_sfx {audio.play(asset_server.load("sfx/enemy_destroyed.ogg")).with_volume(SFX_VOLUME);}
+			if hit_sfx {ev_w_sfx.send(SfxEvent::EnemyDestroyed);}
+		}
+	}
+}
+
+// In hardcore mode touching an enemy costs health, knocks
+// the mech back and makes it briefly invulnerable
+fn mech_contact_damage(
+	hardcore: Res<Hardcore>,
+	mut mech_query: Query<(&mut TruePosition, &mut Mech, Option<&Invulnerable>)>,
+	enemy_query: Query<&TruePosition, (With<Enemy>, Without<Mech>)>,
+	boss_query: Query<&TruePosition, (With<Boss>, Without<Mech>)>,
+	bullet_query: Query<(&TruePosition, &Bullet), Without<Mech>>,
+	mut win_state: ResMut<WinState>,
+	mut next_game_state: ResMut<NextState<GameState>>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_camera: EventWriter<CameraEvent>,
+) {
+	if !hardcore.0 {return};
+	for (mut mech_pos, mut mech, invulnerable) in mech_query.iter_mut() {
+		if !mech.iframes.finished() || invulnerable.is_some() || mech.health == 0 {continue};
+		let Some(enemy_pos) = enemy_query.iter().find(|enemy_pos| (mech_pos.0 - enemy_pos.0).length() < MECH_HIT_DISTANCE)
+		.or_else(|| boss_query.iter().find(|boss_pos| (mech_pos.0 - boss_pos.0).length() < BOSS_RADIUS))
+		.or_else(|| bullet_query.iter()
+			.find(|(bullet_pos, bullet)| bullet.faction == Faction::Enemy && (mech_pos.0 - bullet_pos.0).length() < MECH_HIT_DISTANCE)
+			.map(|(bullet_pos, _)| bullet_pos)) else {continue};
+		mech.health -= 1;
+		mech.iframes = Timer::from_seconds(MECH_IFRAME_DURATION, TimerMode::Once);
+		let knockback = (mech_pos.0 - enemy_pos.0).normalize_or_zero() * MECH_KNOCKBACK;
+		mech_pos.0 = (mech_pos.0 + knockback).clamp(Vec2::new(-72.0, -64.0), Vec2::new(72.0, 64.0));
+		ev_w_camera.send(CameraEvent::Shake(MECH_HIT_TRAUMA));
+		ev_w_camera.send(CameraEvent::HitStop(MECH_HIT_STOP));
+		if mech.health == 0 {
+			ev_w_sfx.send(SfxEvent::EnemyDestroyed);
+			ev_w_particles.send(ParticleEvent{
+				effect: ParticleEffect::EnemyBurst,
+				position: mech_pos.0,
+				direction: Vec2::ZERO,
+			});
+			win_state.0 = 3;
+			next_game_state.set(GameState::Win);
+		} else {
+			ev_w_sfx.send(SfxEvent::Unstun);
+			ev_w_particles.send(ParticleEvent{
+				effect: ParticleEffect::StarSpark,
+				position: mech_pos.0,
+				direction: Vec2::ZERO,
+			});
+		}
+	}
+}
+
+// The mech blinks while it can't be hurt
+fn mech_iframes(
+	time: Res<Time>,
+	mut mech_query: Query<(&mut Mech, &mut Visibility)>,
+) {
+	for (mut mech, mut visibility) in mech_query.iter_mut() {
+		mech.iframes.tick(time.delta());
+		let blink = !mech.iframes.finished() && (mech.iframes.remaining_secs() / MECH_IFRAME_BLINK_SPEED) as usize % 2 == 0;
+		*visibility = if blink {Visibility::Hidden} else {Visibility::Inherited};
+	}
+}
+
+fn health_pips_animate(
+	mech_query: Query<&Mech>,
+	mut pip_query: Query<(&HealthPip, &mut Sprite)>,
+) {
+	for mech in mech_query.iter() {
+		for (pip, mut sprite) in pip_query.iter_mut() {
+			sprite.color = if pip.0 < mech.health {COLOR_D} else {COLOR_B};
+		}
+	}
+}
+
+// Slashing an enemy bullet sends it back the way the
+// slash is facing, faster and now able to hit enemies
+fn reflect_bullets(
+	slash_query: Query<(&GlobalTransform, &Direction, &Slash)>,
+	mut bullet_query: Query<(&TruePosition, &mut Bullet, &mut Sprite)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+) {
+	for (slash_transform, direction, slash) in slash_query.iter() {
+		if !slash.active {continue};
+		for (bullet_pos, mut bullet, mut sprite) in bullet_query.iter_mut() {
+			if bullet.faction != Faction::Enemy {continue};
+			let offset = (slash_transform.translation().xy() - bullet_pos.0).abs();
+			if !offset.cmplt(direction.slash_reach()).all() {continue};
+			let facing = direction.vector();
+			bullet.faction = Faction::Mech;
+			bullet.velocity = facing * bullet.velocity.length() * BULLET_REFLECT_MULTIPLIER;
+			sprite.color = COLOR_C;
+			ev_w_sfx.send(SfxEvent::Pew);
+			ev_w_particles.send(ParticleEvent{
+				effect: ParticleEffect::StarSpark,
+				position: bullet_pos.0,
+				direction: facing,
+			});
 		}
 	}
 }
@@ -343,14 +535,16 @@ fn mech_stun(
 fn mech_slash(
 	keyboard: Res<Input<KeyCode>>,
 	time: Res<Time>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
-	mut mech_query: Query<(&Direction, &mut Mech)>,
+	tuning: Res<GameTuning>,
+	mut mech_query: Query<(&Direction, &mut Mech, Option<&RapidFire>)>,
 	mut slash_query: Query<(&Direction, &mut Slash)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
 ) {
-	for (mech_direction, mut mech) in mech_query.iter_mut() {
-		mech.slash_cooldown.tick(time.delta());
-		mech.shoot_cooldown.tick(time.delta());
+	for (mech_direction, mut mech, rapid_fire) in mech_query.iter_mut() {
+		// Rapid fire makes attack cooldowns run out faster
+		let delta = if rapid_fire.is_some() {time.delta().mul_f32(tuning.rapid_fire_multiplier)} else {time.delta()};
+		mech.slash_cooldown.tick(delta);
+		mech.shoot_cooldown.tick(delta);
 		if (keyboard.pressed(A_BUTTON)
 		|| keyboard.pressed(ALT_A_BUTTON))
 		&& mech.shoot_cooldown.finished()
@@ -358,7 +552,7 @@ fn mech_slash(
 		&& mech.stun_cooldown.finished() 
 		&& mech.beam_cooldown.finished(){
 			mech.slash_cooldown.reset();
-			audio.play(asset_server.load("sfx/slash.ogg")).with_volume(SFX_VOLUME);
+			ev_w_sfx.send(SfxEvent::Slash);
 			for (slash_direction, mut slash) in slash_query.iter_mut() {
 				if *mech_direction == *slash_direction {
 					slash.active = true;
@@ -370,14 +564,16 @@ fn mech_slash(
 
 fn mech_shoot(
 	keyboard: Res<Input<KeyCode>>,
-	audio: Res<Audio>,
 	asset_server: Res<AssetServer>,
 	time: Res<Time>,
+	tuning: Res<GameTuning>,
 	mut commands: Commands,
-	mut mech_query: Query<(&Transform, &Direction, &mut Mech)>,
+	mut mech_query: Query<(&Transform, &Direction, &mut Mech, Option<&RapidFire>)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
 ) {
-	for (transform, direction, mut mech) in mech_query.iter_mut() {
+	for (transform, direction, mut mech, rapid_fire) in mech_query.iter_mut() {
 		// Shoot cooldown ticked in mech_slash since it runs first so that slash takes priority
+		let bullet_speed = if rapid_fire.is_some() {tuning.bullet_speed * tuning.rapid_fire_bullet_multiplier} else {tuning.bullet_speed};
 		if (keyboard.pressed(B_BUTTON)
 		|| keyboard.pressed(ALT_B_BUTTON))
 		&& mech.shoot_cooldown.finished() 
@@ -385,7 +581,7 @@ fn mech_shoot(
 		&& mech.stun_cooldown.finished() 
 		&& mech.beam_cooldown.finished() {
 			mech.shoot_cooldown.reset();
-			audio.play(asset_server.load("sfx/pew.ogg")).with_volume(SFX_VOLUME);
+			ev_w_sfx.send(SfxEvent::Pew);
 			commands
 				.spawn((SpriteBundle {
 					texture: asset_server.load(if *direction == Direction::Left || *direction == Direction::Right {"sprites/bullet_right.png"}
@@ -400,9 +596,10 @@ fn mech_shoot(
 					..default()
 				},
 				Bullet{
+					faction: Faction::Mech,
 					velocity: Vec2::new(
-						if *direction == Direction::Left {-BULLET_SPEED} else if *direction == Direction::Right {BULLET_SPEED} else {0.0},
-						if *direction == Direction::Forward {-BULLET_SPEED} else if *direction == Direction::Backward {BULLET_SPEED} else {0.0},
+						if *direction == Direction::Left {-bullet_speed} else if *direction == Direction::Right {bullet_speed} else {0.0},
+						if *direction == Direction::Forward {-bullet_speed} else if *direction == Direction::Backward {bullet_speed} else {0.0},
 					),
 				},
 				TruePosition(transform.translation.xy()),
@@ -414,149 +611,98 @@ fn mech_shoot(
 
 fn mech_beam(
 	mut beam_charge: ResMut<BeamCharge>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	sprite_atlases: Res<SpriteAtlases>,
 	keyboard: Res<Input<KeyCode>>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
 	mut commands: Commands,
 	mut mech_query: Query<(&Transform, &Direction, &mut Mech)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_camera: EventWriter<CameraEvent>,
 ) {
-	if beam_charge.0 >= BEAM_CHARGE_REQUIREMENT {
-		if (keyboard.pressed(A_BUTTON) || keyboard.pressed(ALT_A_BUTTON)) 
-		&& (keyboard.pressed(B_BUTTON) || keyboard.pressed(ALT_B_BUTTON)) {
-			beam_charge.0 = 0.0;
-			for (transform, direction, mut mech) in mech_query.iter_mut() {
-				mech.beam_cooldown.reset();
-				audio.play(asset_server.load("sfx/beam.ogg")).with_volume(SFX_VOLUME);
-				let offset = match direction {
-					Direction::Forward => Vec2::new(0.0, -80.0),
-					Direction::Backward => Vec2::new(0.0, 80.0),
-					Direction::Left => Vec2::new(-80.0, 0.0),
-					Direction::Right => Vec2::new(80.0, 0.0),
-				};
-				commands
-					.spawn((SpriteSheetBundle {
-						transform: Transform::from_xyz(transform.translation.x + offset.x, transform.translation.y + offset.y, 190.0),
-						texture_atlas: match direction {
-							Direction::Forward => texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/beam_down.png"), Vec2::new(60.0, 144.0), 4, 2, None, None)).clone(),
-							Direction::Backward => texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/beam_up.png"), Vec2::new(60.0, 144.0), 4, 2, None, None)).clone(),
-							Direction::Left => texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/beam_left.png"), Vec2::new(144.0, 60.0), 2, 4, None, None)).clone(),
-							Direction::Right => texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/beam_right.png"), Vec2::new(144.0, 60.0), 2, 4, None, None)).clone(),
-						},
-						sprite: TextureAtlasSprite{
-							index: 0,
-							custom_size: match direction {
-								Direction::Forward => Some(Vec2::new(60.0, 144.0)),
-								Direction::Backward => Some(Vec2::new(60.0, 144.0)),
-								Direction::Left => Some(Vec2::new(144.0, 60.0)),
-								Direction::Right => Some(Vec2::new(144.0, 60.0)),
-							},
-							..default()
+	// Fire the strongest beam the charge allows, partial charges give smaller beams
+	let Some(level) = BEAM_CHARGE_LEVELS.iter().rposition(|requirement| beam_charge.0 >= *requirement) else {return};
+	if (keyboard.pressed(A_BUTTON) || keyboard.pressed(ALT_A_BUTTON)) 
+	&& (keyboard.pressed(B_BUTTON) || keyboard.pressed(ALT_B_BUTTON)) {
+		for (transform, direction, mut mech) in mech_query.iter_mut() {
+			if !mech.beam_cooldown.finished() {continue};
+			beam_charge.0 -= BEAM_CHARGE_LEVELS[level];
+			mech.beam_cooldown.reset();
+			let size = BEAM_SIZES[level];
+			let power = (level + 1) as f32 / BEAM_CHARGE_LEVELS.len() as f32;
+			ev_w_sfx.send(SfxEvent::Beam);
+			ev_w_camera.send(CameraEvent::Shake(BEAM_TRAUMA * power));
+			ev_w_camera.send(CameraEvent::HitStop(BEAM_HIT_STOP * power));
+			let offset = direction.vector() * (size.y / 2.0 + BEAM_GAP);
+			commands
+				.spawn((SpriteSheetBundle {
+					transform: Transform::from_xyz(transform.translation.x + offset.x, transform.translation.y + offset.y, 190.0),
+					texture_atlas: sprite_atlases.beam(*direction),
+					sprite: TextureAtlasSprite{
+						index: 0,
+						custom_size: match direction {
+							Direction::Forward | Direction::Backward => Some(size),
+							Direction::Left | Direction::Right => Some(Vec2::new(size.y, size.x)),
 						},
 						..default()
 					},
-					Beam(*direction),
-					AnimationTimer(Timer::from_seconds(BEAM_SPEED, TimerMode::Repeating)),
-					DespawnOnExitGameState,
-				));
-			}
+					..default()
+				},
+				Beam {
+					direction: *direction,
+					size,
+				},
+				AnimationTimer(Timer::from_seconds(BEAM_SPEED, TimerMode::Repeating)),
+				DespawnOnExitGameState,
+			));
+			ev_w_particles.send(ParticleEvent{
+				effect: ParticleEffect::BeamResidue,
+				position: transform.translation.truncate() + offset,
+				direction: offset,
+			});
 		}
 	}
 }
 
-fn disable_retaliate(
-	keyboard: Res<Input<KeyCode>>,
-	asset_server: Res<AssetServer>,
-	audio: Res<Audio>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+fn toggle_retaliate(
+	sprite_atlases: Res<SpriteAtlases>,
 	mut commands: Commands,
 	mut retaliate: ResMut<Retaliate>,
-	mut secret_code: ResMut<SecretCode>,
+	mut ev_r_secret: EventReader<SecretEvent>,
 ) {
-	if keyboard.just_pressed(UP_BUTTON)
-	|| keyboard.just_pressed(ALT_UP_BUTTON) {
-		if secret_code.0 == 0 
-		|| secret_code.0 == 1 
-		|| secret_code.0 == 6 
-		|| secret_code.0 == 8 {
-			secret_code.0 += 1
-		} else {
-			secret_code.0 = 0;
-		}
-	} else if keyboard.just_pressed(DOWN_BUTTON)
-	|| keyboard.just_pressed(ALT_DOWN_BUTTON) {
-		if secret_code.0 == 3 
-		|| secret_code.0 == 5 {
-			secret_code.0 += 1
-		} else {
-			secret_code.0 = 0;
-		}
-	} else if keyboard.just_pressed(LEFT_BUTTON)
-	|| keyboard.just_pressed(ALT_LEFT_BUTTON) {
-		if secret_code.0 == 2 
-		|| secret_code.0 == 4
-		|| secret_code.0 == 7 {
-			secret_code.0 += 1
-		} else {
-			secret_code.0 = 0;
-		}
-	} else if keyboard.just_pressed(RIGHT_BUTTON)
-	|| keyboard.just_pressed(ALT_RIGHT_BUTTON) {
-		if secret_code.0 == 9 {
-			secret_code.0 += 1
-		} else {
-			secret_code.0 = 0;
-		}
-	} else if keyboard.just_pressed(SELECT_BUTTON) 
-	|| keyboard.just_pressed(ALT_SELECT_BUTTON)
-	|| keyboard.just_pressed(ALT_ALT_SELECT_BUTTON){
-		if secret_code.0 == 10 {
-			secret_code.0 += 1
-		} else {
-			secret_code.0 = 0;
-		}
-	} else if keyboard.just_pressed(START_BUTTON) 
-	|| keyboard.just_pressed(ALT_START_BUTTON)
-	|| keyboard.just_pressed(ALT_ALT_START_BUTTON) {
-		if secret_code.0 == 11 {
-			audio.play(asset_server.load("sfx/secret.ogg")).with_volume(SFX_VOLUME);
-			secret_code.0 = 0;
-			retaliate.0 = !retaliate.0;
-			if retaliate.0 {
-				//println!("Override Accepted. Retaliation enabled.");
-				commands
-					.spawn((SpriteSheetBundle {
-						transform: Transform::from_xyz(0.0, 0.0, 800.0),
-						texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("text/retaliation.png"), Vec2::new(92.0, 12.0), 1, 2, None, None)).clone(),
-						sprite: TextureAtlasSprite{
-							index: 1,
-							custom_size: Some(Vec2::new(92.0, 12.0)),
-							..default()
-						},
+	for _ in ev_r_secret.iter().filter(|ev| ev.0 == Secret::Retaliate) {
+		retaliate.0 = !retaliate.0;
+		if retaliate.0 {
+			//println!("Override Accepted. Retaliation enabled.");
+			commands
+				.spawn((SpriteSheetBundle {
+					transform: Transform::from_xyz(0.0, 0.0, 800.0),
+					texture_atlas: sprite_atlases.retaliation_text.clone(),
+					sprite: TextureAtlasSprite{
+						index: 1,
+						custom_size: Some(Vec2::new(92.0, 12.0)),
 						..default()
 					},
-					RetaliateText(Timer::from_seconds(RETALIATE_DURATION, TimerMode::Once)),
-				));
-			} else {
-				//println!("Override Accepted. Retaliation disabled.");
-				commands
-					.spawn((SpriteSheetBundle {
-						transform: Transform::from_xyz(0.0, 0.0, 800.0),
-						texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("text/retaliation.png"), Vec2::new(92.0, 12.0), 1, 2, None, None)).clone(),
-						sprite: TextureAtlasSprite{
-							index: 0,
-							custom_size: Some(Vec2::new(92.0, 12.0)),
-							..default()
-						},
+					..default()
+				},
+				RetaliateText(Timer::from_seconds(RETALIATE_DURATION, TimerMode::Once)),
+			));
+		} else {
+			//println!("Override Accepted. Retaliation disabled.");
+			commands
+				.spawn((SpriteSheetBundle {
+					transform: Transform::from_xyz(0.0, 0.0, 800.0),
+					texture_atlas: sprite_atlases.retaliation_text.clone(),
+					sprite: TextureAtlasSprite{
+						index: 0,
+						custom_size: Some(Vec2::new(92.0, 12.0)),
 						..default()
 					},
-					RetaliateText(Timer::from_seconds(RETALIATE_DURATION, TimerMode::Once)),
-				));
-			}
-		} else {
-			secret_code.0 = 0;
+					..default()
+				},
+				RetaliateText(Timer::from_seconds(RETALIATE_DURATION, TimerMode::Once)),
+			));
 		}
-	} 
+	}
 }
 
 fn hide_retaliation_text(
@@ -1,8 +1,7 @@
 // Import Bevy game engine essentials
 use bevy::{prelude::*, app::AppExit};
-use bevy_kira_audio::{Audio, AudioControl};
 // Import components, resources, and events
-use crate::derivables::*;
+use crate::{derivables::*, catalogue::project_constellation, menu_widget::navigate_menus};
 
 // Plugin for generating the main menu
 pub struct MenuPlugin;
@@ -16,7 +15,8 @@ impl Plugin for MenuPlugin {
 			))
 			.add_systems(Update,(
 				flash_start_text,
-				advance_menu,
+				advance_menu.after(navigate_menus),
+				option_text.after(advance_menu),
 			).run_if(in_state(GameState::Menu)))
 		;
 	}
@@ -25,6 +25,9 @@ impl Plugin for MenuPlugin {
 fn spawn_menu(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
+	sprite_atlases: Res<SpriteAtlases>,
+	eight_way: Res<EightWay>,
+	drift: Res<Drift>,
 ) {
 	commands.spawn((
 		SpriteBundle{
@@ -52,26 +55,112 @@ fn spawn_menu(
 		StartText,
 		DespawnOnExitGameState,
 	));
+
+	commands.spawn((
+		SpriteSheetBundle{
+			transform: Transform::from_xyz(36.0, 6.0, 5.0),
+			texture_atlas: sprite_atlases.controls_text.clone(),
+			sprite: TextureAtlasSprite{
+				index: if eight_way.0 {1} else {0},
+				custom_size: Some(Vec2::new(56.0, 8.0)),
+				..default()
+			},
+			..default()
+		},
+		ControlsText,
+		DespawnOnExitGameState,
+	));
+
+	commands.spawn((
+		SpriteSheetBundle{
+			transform: Transform::from_xyz(36.0, -6.0, 5.0),
+			texture_atlas: sprite_atlases.movement_text.clone(),
+			sprite: TextureAtlasSprite{
+				index: if drift.0 {1} else {0},
+				custom_size: Some(Vec2::new(56.0, 8.0)),
+				..default()
+			},
+			..default()
+		},
+		MovementText,
+		DespawnOnExitGameState,
+	));
+
+	// Shake and hit stop options under the movement options
+	for line in 0..2 {
+		for column in 0..14 {
+			commands.spawn((
+				SpriteSheetBundle {
+					transform: Transform::from_xyz(10.0 + 4.0 * column as f32, -18.0 - 12.0 * line as f32, 5.0),
+					texture_atlas: sprite_atlases.font.clone(),
+					sprite: TextureAtlasSprite{
+						index: 0,
+						custom_size: Some(Vec2::new(4.0, 6.0)),
+						..default()
+					},
+					..default()
+				},
+				OptionGlyph{line, column},
+				DespawnOnExitGameState,
+			));
+		}
+	}
+
+	// Start, then the controls, movement and screen effect toggles underneath
+	commands.spawn((
+		SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, 5.0)),
+		MenuWidget::new(vec![
+			MenuItem::new(Rect::new(8.0, 14.0, 64.0, 22.0)),
+			MenuItem::new(Rect::new(8.0, 2.0, 64.0, 10.0)),
+			MenuItem::new(Rect::new(8.0, -10.0, 64.0, -2.0)),
+			MenuItem::new(Rect::new(8.0, -22.0, 64.0, -14.0)),
+			MenuItem::new(Rect::new(8.0, -34.0, 64.0, -26.0)),
+		], 1, true),
+		TitleMenu,
+		DespawnOnExitGameState,
+	));
 }
 
+// The random constellation is usually a real one from the star
+// catalogue, and so is endless, falling back to scattered stars
+// if the catalogue hasn't loaded
 fn generate_random_constellation(
 	mut level_layout: ResMut<LevelLayout>,
 	mut milky: ResMut<Milky>,
+	catalogue_assets: Res<Assets<StarCatalogue>>,
+	catalogue_handle: Res<StarCatalogueHandle>,
 ) {
-	let mut random = Vec::new();
+	let catalogue = catalogue_assets.get(&catalogue_handle.0)
+		.filter(|catalogue| !catalogue.constellations.is_empty());
+	let pick = || catalogue.map(|catalogue| {
+		&catalogue.constellations[rand::Rng::gen_range(&mut rand::thread_rng(), 0..catalogue.constellations.len())]
+	});
+
 	if rand::random::<f32>() < 0.95 {
-		let total_stars = rand::Rng::gen_range(&mut rand::thread_rng(), 2..9);
-		for _ in 0..total_stars {
-			random.push(Vec2::new(((rand::random::<f32>() - 0.5) * 120.0).round() + 0.5, ((rand::random::<f32>() - 0.5) * 80.0).round() + 0.5));
+		if let Some(real) = pick() {
+			project_constellation(real, &mut level_layout.constellations[4]);
+		} else {
+			let mut random = Vec::new();
+			let total_stars = rand::Rng::gen_range(&mut rand::thread_rng(), 2..9);
+			for _ in 0..total_stars {
+				random.push(Vec2::new(((rand::random::<f32>() - 0.5) * 120.0).round() + 0.5, ((rand::random::<f32>() - 0.5) * 80.0).round() + 0.5));
+			}
+			// Join the stars up from left to right
+			random.sort_by(|a, b| a.x.total_cmp(&b.x));
+			level_layout.constellations[4].edges = (1..random.len()).map(|index| (index - 1, index)).collect();
+			level_layout.constellations[4].stars = random;
 		}
 		milky.0 = false;
 	} else {
 		// The Sun
-		random.push(Vec2::new(0.5, 0.5));
+		level_layout.constellations[4].stars = vec![Vec2::new(0.5, 0.5)];
+		level_layout.constellations[4].edges.clear();
 		milky.0 = true;
 	}
 
-	level_layout.constellations[4] = random;
+	if let Some(real) = pick() {
+		project_constellation(real, &mut level_layout.constellations[5]);
+	}
 }
 
 fn flash_start_text(
@@ -91,22 +180,80 @@ fn flash_start_text(
 	}
 }
 
+fn option_text(
+	screen_effect_settings: Res<ScreenEffectSettings>,
+	new_glyph_query: Query<(), Added<OptionGlyph>>,
+	mut glyph_query: Query<(&mut TextureAtlasSprite, &mut Visibility, &OptionGlyph)>,
+) {
+	if !screen_effect_settings.is_changed() && new_glyph_query.is_empty() {return};
+
+	let lines = [
+		match screen_effect_settings.shake {
+			shake if shake > 0.75 => "SHAKE FULL".to_string(),
+			shake if shake > 0.25 => "SHAKE HALF".to_string(),
+			_ => "SHAKE OFF".to_string(),
+		},
+		format!("HIT STOP {}", if screen_effect_settings.hit_stop {"ON"} else {"OFF"}),
+	];
+	for (mut sprite, mut visibility, glyph) in glyph_query.iter_mut() {
+		let character = lines[glyph.line].chars().nth(glyph.column).unwrap_or(' ');
+		sprite.index = FONT_CHARS.find(character).unwrap_or(0);
+		*visibility = if character != ' ' {Visibility::Visible} else {Visibility::Hidden};
+	}
+}
+
 fn advance_menu(
 	keyboard: Res<Input<KeyCode>>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
+	mut eight_way: ResMut<EightWay>,
+	mut drift: ResMut<Drift>,
+	mut screen_effect_settings: ResMut<ScreenEffectSettings>,
+	menu_query: Query<(), With<TitleMenu>>,
+	mut controls_text_query: Query<&mut TextureAtlasSprite, With<ControlsText>>,
+	mut movement_text_query: Query<&mut TextureAtlasSprite, (With<MovementText>, Without<ControlsText>)>,
+	mut ev_r_menu: EventReader<MenuEvent>,
 	mut ev_w_exit: EventWriter<AppExit>,
 	mut next_game_state: ResMut<NextState<GameState>>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
 ) {
-	if keyboard.just_pressed(START_BUTTON) 
+	for ev in ev_r_menu.iter().filter(|ev| menu_query.contains(ev.menu)) {
+		match ev.action {
+			MenuAction::Confirm(0) => {
+				next_game_state.set(GameState::LevelSelect);
+			}
+			MenuAction::Confirm(1) => {
+				// Switch between four and eight way movement
+				eight_way.0 = !eight_way.0;
+				for mut sprite in controls_text_query.iter_mut() {
+					sprite.index = if eight_way.0 {1} else {0};
+				}
+			}
+			MenuAction::Confirm(2) => {
+				// Switch between snappy and drift movement
+				drift.0 = !drift.0;
+				for mut sprite in movement_text_query.iter_mut() {
+					sprite.index = if drift.0 {1} else {0};
+				}
+			}
+			MenuAction::Confirm(3) => {
+				// Full, half, then no screen shake
+				screen_effect_settings.shake = if screen_effect_settings.shake > 0.75 {0.5}
+					else if screen_effect_settings.shake > 0.25 {0.0}
+					else {1.0};
+			}
+			MenuAction::Confirm(_) => {
+				screen_effect_settings.hit_stop = !screen_effect_settings.hit_stop;
+			}
+			MenuAction::Cancel => {
+				ev_w_exit.send(AppExit);
+			}
+		}
+	}
+
+	// Start skips straight to level select from anywhere in the menu
+	if keyboard.just_pressed(START_BUTTON)
 	|| keyboard.just_pressed(ALT_START_BUTTON)
-	|| keyboard.just_pressed(ALT_ALT_START_BUTTON)
-	|| keyboard.just_pressed(A_BUTTON)
-	|| keyboard.just_pressed(ALT_A_BUTTON) {
-		audio.play(asset_server.load("sfx/ui_select.ogg")).with_volume(SFX_VOLUME);
+	|| keyboard.just_pressed(ALT_ALT_START_BUTTON) {
+		ev_w_sfx.send(SfxEvent::UiSelect);
 		next_game_state.set(GameState::LevelSelect);
-	} else if keyboard.just_pressed(B_BUTTON)
-	|| keyboard.just_pressed(ALT_B_BUTTON) {
-		ev_w_exit.send(AppExit);
 	}
-}
\ No newline at end of file
+}
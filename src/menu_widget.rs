@@ -0,0 +1,184 @@
+// Import Bevy game engine essentials
+use bevy::prelude::*;
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for the menus shared by the title, level select and
+// pause screens, each screen lays out its items over its own
+// artwork and handles the menu events it sends
+pub struct MenuWidgetPlugin;
+
+impl Plugin for MenuWidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_systems(Update, (
+				spawn_menu_cursor,
+				navigate_menus,
+				animate_menu_cursor.after(navigate_menus),
+			))
+		;
+	}
+}
+
+// The cursor is a frame drawn around the selected item,
+// with a plate underneath it for the item's label
+fn spawn_menu_cursor(
+	mut commands: Commands,
+	sprite_atlases: Res<SpriteAtlases>,
+	menu_query: Query<(Entity, &MenuWidget), Added<MenuWidget>>,
+) {
+	for (entity, menu) in menu_query.iter() {
+		let label_length = menu.items.iter().map(|item| item.label.chars().count()).max().unwrap_or(0);
+		commands.entity(entity).with_children(|parent| {
+			for side in 0..4 {
+				parent.spawn((
+					SpriteBundle {
+						transform: Transform::from_xyz(0.0, 0.0, 1.0),
+						sprite: Sprite {
+							color: COLOR_D,
+							..default()
+						},
+						..default()
+					},
+					MenuCursor(side),
+				));
+			}
+			if label_length == 0 {return};
+			parent.spawn((
+				SpriteBundle {
+					transform: Transform::from_xyz(0.0, 0.0, 1.0),
+					sprite: Sprite {
+						color: Color::BLACK,
+						..default()
+					},
+					..default()
+				},
+				MenuLabel,
+			));
+			for column in 0..label_length {
+				parent.spawn((
+					SpriteSheetBundle {
+						transform: Transform::from_xyz(0.0, 0.0, 2.0),
+						texture_atlas: sprite_atlases.font.clone(),
+						sprite: TextureAtlasSprite{
+							index: 0,
+							custom_size: Some(Vec2::new(4.0, 6.0)),
+							..default()
+						},
+						..default()
+					},
+					MenuGlyph(column),
+				));
+			}
+		});
+	}
+}
+
+// Up and down move by a row and left and right by an item, A
+// confirms and B cancels
+pub fn navigate_menus(
+	keyboard: Res<Input<KeyCode>>,
+	mut menu_query: Query<(Entity, &mut MenuWidget)>,
+	mut ev_w_menu: EventWriter<MenuEvent>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+) {
+	for (entity, mut menu) in menu_query.iter_mut() {
+		if menu.items.is_empty() {continue};
+		let columns = menu.columns as isize;
+		let offset = if keyboard.just_pressed(UP_BUTTON)
+		|| keyboard.just_pressed(ALT_UP_BUTTON) {
+			-columns
+		} else if keyboard.just_pressed(DOWN_BUTTON)
+		|| keyboard.just_pressed(ALT_DOWN_BUTTON) {
+			columns
+		} else if (keyboard.just_pressed(LEFT_BUTTON)
+		|| keyboard.just_pressed(ALT_LEFT_BUTTON)) && columns > 1 {
+			-1
+		} else if (keyboard.just_pressed(RIGHT_BUTTON)
+		|| keyboard.just_pressed(ALT_RIGHT_BUTTON)) && columns > 1 {
+			1
+		} else {
+			0
+		};
+		if offset != 0 && menu.step(offset) {
+			ev_w_sfx.send(SfxEvent::UiSelect);
+		}
+
+		if keyboard.just_pressed(A_BUTTON)
+		|| keyboard.just_pressed(ALT_A_BUTTON) {
+			if menu.items[menu.selected].enabled {
+				ev_w_menu.send(MenuEvent{
+					menu: entity,
+					action: MenuAction::Confirm(menu.selected),
+				});
+				ev_w_sfx.send(SfxEvent::UiSelect);
+			}
+		} else if keyboard.just_pressed(B_BUTTON)
+		|| keyboard.just_pressed(ALT_B_BUTTON) {
+			ev_w_menu.send(MenuEvent{
+				menu: entity,
+				action: MenuAction::Cancel,
+			});
+			ev_w_sfx.send(SfxEvent::UiSelect);
+		}
+	}
+}
+
+// Slides the cursor towards the selected item and flashes it,
+// keeping every side on whole pixels
+fn animate_menu_cursor(
+	time: Res<Time>,
+	mut menu_query: Query<&mut MenuWidget>,
+	mut cursor_query: Query<(&Parent, &MenuCursor, &mut Transform, &mut Sprite)>,
+	mut label_query: Query<(&Parent, &mut Transform, &mut Sprite, &mut Visibility), (With<MenuLabel>, Without<MenuCursor>)>,
+	mut glyph_query: Query<(&Parent, &MenuGlyph, &mut Transform, &mut TextureAtlasSprite, &mut Visibility), (Without<MenuCursor>, Without<MenuLabel>)>,
+) {
+	for mut menu in menu_query.iter_mut() {
+		let Some(target) = menu.items.get(menu.selected).map(|item| item.rect) else {continue};
+		let amount = (MENU_CURSOR_SPEED * time.delta_seconds()).min(1.0);
+		let cursor = Rect::from_corners(menu.cursor.min.lerp(target.min, amount), menu.cursor.max.lerp(target.max, amount));
+		menu.cursor = if cursor.min.distance(target.min) < 0.5 && cursor.max.distance(target.max) < 0.5 {target} else {cursor};
+	}
+
+	let shade = if (time.elapsed_seconds() / MENU_CURSOR_FLASH) as u32 % 2 == 0 {COLOR_D} else {COLOR_C};
+	for (parent, side, mut transform, mut sprite) in cursor_query.iter_mut() {
+		let Ok(menu) = menu_query.get(parent.get()) else {continue};
+		let inner = Rect::from_corners(menu.cursor.min.round() - MENU_CURSOR_GAP, menu.cursor.max.round() + MENU_CURSOR_GAP);
+		let (position, size) = match side.0 {
+			0 => (Vec2::new(inner.center().x, inner.max.y + 0.5), Vec2::new(inner.width() + 2.0, 1.0)),
+			1 => (Vec2::new(inner.center().x, inner.min.y - 0.5), Vec2::new(inner.width() + 2.0, 1.0)),
+			2 => (Vec2::new(inner.min.x - 0.5, inner.center().y), Vec2::new(1.0, inner.height())),
+			_ => (Vec2::new(inner.max.x + 0.5, inner.center().y), Vec2::new(1.0, inner.height())),
+		};
+		transform.translation = position.extend(transform.translation.z);
+		sprite.custom_size = Some(size);
+		if sprite.color != shade {
+			sprite.color = shade;
+		}
+	}
+
+	// The label sits just below the cursor
+	let label_centre = |menu: &MenuWidget| {
+		let bottom = menu.cursor.min.y.round() - MENU_CURSOR_GAP - 1.0;
+		Vec2::new(menu.cursor.center().x, bottom - 5.0)
+	};
+	for (parent, mut transform, mut sprite, mut visibility) in label_query.iter_mut() {
+		let Ok(menu) = menu_query.get(parent.get()) else {continue};
+		let label = &menu.items[menu.selected].label;
+		let centre = label_centre(menu);
+		let width = 4.0 * label.chars().count() as f32 + 2.0;
+		transform.translation = Vec3::new((centre.x - width / 2.0).round() + width / 2.0, centre.y, transform.translation.z);
+		sprite.custom_size = Some(Vec2::new(width, 8.0));
+		*visibility = if label.is_empty() {Visibility::Hidden} else {Visibility::Visible};
+	}
+	for (parent, glyph, mut transform, mut sprite, mut visibility) in glyph_query.iter_mut() {
+		let Ok(menu) = menu_query.get(parent.get()) else {continue};
+		let label = &menu.items[menu.selected].label;
+		let centre = label_centre(menu);
+		let left = (centre.x - 2.0 * label.chars().count() as f32).round();
+		let character = label.chars().nth(glyph.0).unwrap_or(' ');
+		transform.translation = Vec3::new(left + 2.0 + 4.0 * glyph.0 as f32, centre.y, transform.translation.z);
+		sprite.index = FONT_CHARS.find(character).unwrap_or(0);
+		*visibility = if character != ' ' {Visibility::Visible} else {Visibility::Hidden};
+	}
+}
@@ -0,0 +1,118 @@
+// Import Bevy game engine essentials
+use bevy::prelude::*;
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for small pixel particle effects, particles are
+// spawned once up front and reused to avoid constantly
+// spawning and despawning entities
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_systems(Startup, (
+				spawn_particle_pool,
+			))
+			.add_systems(Update, (
+				emit_particles,
+				update_particles.after(emit_particles),
+			).run_if(in_state(GameState::Level))
+			.run_if(in_state(PauseState::Unpaused)))
+			.add_systems(OnExit(GameState::Level), (
+				recycle_particles,
+			))
+		;
+	}
+}
+
+fn spawn_particle_pool(
+	mut commands: Commands,
+) {
+	let mut pool = Vec::new();
+	for _ in 0..PARTICLE_POOL_SIZE {
+		pool.push(commands
+			.spawn((SpriteBundle {
+				transform: Transform::from_xyz(0.0, 0.0, 175.0),
+				visibility: Visibility::Hidden,
+				..default()
+			},
+			Particle{
+				active: false,
+				effect: ParticleEffect::EnemyBurst,
+				velocity: Vec2::ZERO,
+				lifetime: Timer::from_seconds(1.0, TimerMode::Once),
+			},
+			TruePosition(Vec2::ZERO),
+		)).id());
+	}
+	commands.insert_resource(ParticlePool(pool));
+}
+
+fn emit_particles(
+	mut ev_r_particles: EventReader<ParticleEvent>,
+	mut particle_pool: ResMut<ParticlePool>,
+	mut particle_query: Query<(&mut Particle, &mut TruePosition, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+	for ev in ev_r_particles.iter() {
+		let spec = ev.effect.spec();
+		let base_angle = ev.direction.y.atan2(ev.direction.x);
+		let along = ev.direction.normalize_or_zero();
+		for _ in 0..spec.count {
+			// Out of particles, skip rather than grow the pool
+			let Some(entity) = particle_pool.0.pop() else {return};
+			let Ok((mut particle, mut pos, mut transform, mut sprite, mut visibility)) = particle_query.get_mut(entity) else {continue};
+			let angle = base_angle + ((rand::random::<f32>() - 0.5) * 2.0 * spec.spread).to_radians();
+			let speed = spec.speed + (rand::random::<f32>() - 0.5) * 2.0 * spec.speed_variation;
+			particle.active = true;
+			particle.effect = ev.effect;
+			particle.velocity = Vec2::from_angle(angle) * speed;
+			particle.lifetime = Timer::from_seconds(spec.lifetime, TimerMode::Once);
+			pos.0 = ev.position + along * (rand::random::<f32>() - 0.5) * spec.length;
+			transform.translation.x = pos.0.x.round();
+			transform.translation.y = pos.0.y.round();
+			sprite.color = spec.shades[0];
+			sprite.custom_size = Some(Vec2::new(spec.size, spec.size));
+			*visibility = Visibility::Visible;
+		}
+	}
+}
+
+fn update_particles(
+	time: Res<Time>,
+	mut particle_pool: ResMut<ParticlePool>,
+	mut particle_query: Query<(Entity, &mut Particle, &mut TruePosition, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+	for (entity, mut particle, mut pos, mut transform, mut sprite, mut visibility) in particle_query.iter_mut() {
+		if !particle.active {continue};
+		particle.lifetime.tick(time.delta());
+		if particle.lifetime.finished() {
+			particle.active = false;
+			*visibility = Visibility::Hidden;
+			particle_pool.0.push(entity);
+			continue;
+		}
+		let spec = particle.effect.spec();
+		// Fade through the effect's shades instead of alpha
+		// blending so particles stay within the palette
+		let shade = (particle.lifetime.percent() * spec.shades.len() as f32) as usize;
+		sprite.color = spec.shades[shade.min(spec.shades.len() - 1)];
+		particle.velocity = particle.velocity * (1.0 - spec.drag * time.delta_seconds()).max(0.0);
+		pos.0 += particle.velocity * time.delta_seconds();
+		transform.translation.x = pos.0.x.round();
+		transform.translation.y = pos.0.y.round();
+	}
+}
+
+fn recycle_particles(
+	mut particle_pool: ResMut<ParticlePool>,
+	mut particle_query: Query<(Entity, &mut Particle, &mut Visibility)>,
+) {
+	for (entity, mut particle, mut visibility) in particle_query.iter_mut() {
+		if particle.active {
+			particle.active = false;
+			*visibility = Visibility::Hidden;
+			particle_pool.0.push(entity);
+		}
+	}
+}
@@ -0,0 +1,182 @@
+use std::ops::DerefMut;
+
+// Import Bevy game engine essentials
+use bevy::prelude::*;
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for items dropped by defeated enemies
+// and the timed buffs they give the mech
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_systems(OnEnter(GameState::Level), (
+				spawn_buff_icons,
+			))
+			.add_systems(Update, (
+				drop_pickups,
+				expire_pickups,
+				collect_pickups,
+				tick_buff::<RapidFire>,
+				tick_buff::<SpeedBoost>,
+				tick_buff::<Invulnerable>,
+				update_buff_icons,
+			).run_if(in_state(GameState::Level))
+			.run_if(in_state(PauseState::Unpaused)))
+		;
+	}
+}
+
+fn drop_pickups(
+	mut commands: Commands,
+	mut ev_r_defeated: EventReader<EnemyDefeated>,
+	sprite_atlases: Res<SpriteAtlases>,
+	tuning: Res<GameTuning>,
+	mut rng: ResMut<GameRng>,
+) {
+	for ev in ev_r_defeated.iter() {
+		if rand::Rng::gen::<f32>(&mut rng.0) >= tuning.pickup_drop_chance {continue};
+		let kind = PickupKind::ALL[rand::Rng::gen_range(&mut rng.0, 0..PickupKind::ALL.len())];
+		// Keep pickups on screen so they can always be collected
+		let position = Vec2::new(ev.position.x.clamp(-72.0, 72.0), ev.position.y.clamp(-64.0, 64.0));
+		commands
+			.spawn((SpriteSheetBundle {
+				transform: Transform::from_xyz(position.x.round(), position.y.round(), 140.0),
+				texture_atlas: sprite_atlases.pickup.clone(),
+				sprite: TextureAtlasSprite{
+					index: kind.sprite_index(),
+					custom_size: Some(Vec2::new(8.0, 8.0)),
+					..default()
+				},
+				..default()
+			},
+			Pickup{
+				kind: kind,
+				lifetime: Timer::from_seconds(tuning.pickup_lifetime, TimerMode::Once),
+			},
+			TruePosition(position),
+			DespawnOnExitGameState,
+		));
+	}
+}
+
+// Pickups blink when they are about to disappear
+fn expire_pickups(
+	time: Res<Time>,
+	mut commands: Commands,
+	mut pickup_query: Query<(Entity, &mut Pickup, &mut Visibility)>,
+) {
+	for (entity, mut pickup, mut visibility) in pickup_query.iter_mut() {
+		pickup.lifetime.tick(time.delta());
+		if pickup.lifetime.finished() {
+			commands.entity(entity).despawn_recursive();
+		} else if pickup.lifetime.remaining_secs() < PICKUP_BLINK_TIME {
+			let blink = (pickup.lifetime.remaining_secs() / PICKUP_BLINK_SPEED) as usize % 2 == 0;
+			*visibility = if blink {Visibility::Hidden} else {Visibility::Visible};
+		}
+	}
+}
+
+fn collect_pickups(
+	mut commands: Commands,
+	mut beam_charge: ResMut<BeamCharge>,
+	tuning: Res<GameTuning>,
+	mut star_query: Query<&mut Star>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mech_query: Query<(Entity, &TruePosition), With<Mech>>,
+	pickup_query: Query<(Entity, &TruePosition, &Pickup)>,
+) {
+	for (mech_entity, mech_pos) in mech_query.iter() {
+		for (entity, pickup_pos, pickup) in pickup_query.iter() {
+			if (mech_pos.0 - pickup_pos.0).length() >= PICKUP_RADIUS {continue};
+			commands.entity(entity).despawn_recursive();
+			ev_w_sfx.send(SfxEvent::UiSelect);
+			match pickup.kind {
+				PickupKind::BeamCharge => beam_charge.add(tuning.pickup_beam_charge),
+				PickupKind::RapidFire => {
+					commands.entity(mech_entity).insert(RapidFire(Timer::from_seconds(tuning.buff_duration, TimerMode::Once)));
+				},
+				PickupKind::SpeedBoost => {
+					commands.entity(mech_entity).insert(SpeedBoost(Timer::from_seconds(tuning.buff_duration, TimerMode::Once)));
+				},
+				PickupKind::Invulnerable => {
+					commands.entity(mech_entity).insert(Invulnerable(Timer::from_seconds(tuning.buff_duration, TimerMode::Once)));
+				},
+				PickupKind::StarHeal => {
+					for mut star in star_query.iter_mut() {
+						star.heal(tuning.pickup_star_heal);
+					}
+				},
+				PickupKind::StarShield => {
+					for mut star in star_query.iter_mut() {
+						star.shield = tuning.star_shield_duration;
+					}
+				},
+			}
+		}
+	}
+}
+
+// Generic function for counting down a timed buff
+// and removing it from the mech once it runs out
+fn tick_buff<T: Component + DerefMut<Target = Timer>>(
+	time: Res<Time>,
+	mut commands: Commands,
+	mut buff_query: Query<(Entity, &mut T)>,
+) {
+	for (entity, mut buff) in buff_query.iter_mut() {
+		buff.tick(time.delta());
+		if buff.finished() {
+			commands.entity(entity).remove::<T>();
+		}
+	}
+}
+
+fn spawn_buff_icons(
+	mut commands: Commands,
+	sprite_atlases: Res<SpriteAtlases>,
+) {
+	let buffs = [PickupKind::RapidFire, PickupKind::SpeedBoost, PickupKind::Invulnerable];
+	for (i, kind) in buffs.iter().enumerate() {
+		commands
+			.spawn((SpriteSheetBundle {
+				transform: Transform::from_xyz(-74.0 + 10.0 * i as f32, -66.0, 300.0),
+				texture_atlas: sprite_atlases.pickup.clone(),
+				sprite: TextureAtlasSprite{
+					index: kind.sprite_index(),
+					custom_size: Some(Vec2::new(8.0, 8.0)),
+					..default()
+				},
+				visibility: Visibility::Hidden,
+				..default()
+			},
+			BuffIcon(*kind),
+			DespawnOnExitGameState,
+		));
+	}
+}
+
+// Show an icon in the corner for each active buff,
+// blinking when the buff is about to run out
+fn update_buff_icons(
+	mech_query: Query<(Option<&RapidFire>, Option<&SpeedBoost>, Option<&Invulnerable>), With<Mech>>,
+	mut icon_query: Query<(&BuffIcon, &mut Visibility)>,
+) {
+	for (rapid_fire, speed_boost, invulnerable) in mech_query.iter() {
+		for (icon, mut visibility) in icon_query.iter_mut() {
+			let remaining = match icon.0 {
+				PickupKind::RapidFire => rapid_fire.map(|buff| buff.remaining_secs()),
+				PickupKind::SpeedBoost => speed_boost.map(|buff| buff.remaining_secs()),
+				PickupKind::Invulnerable => invulnerable.map(|buff| buff.remaining_secs()),
+				_ => None,
+			};
+			*visibility = match remaining {
+				Some(remaining) if remaining > BUFF_BLINK_TIME => Visibility::Visible,
+				Some(remaining) if (remaining / PICKUP_BLINK_SPEED) as usize % 2 == 1 => Visibility::Visible,
+				_ => Visibility::Hidden,
+			};
+		}
+	}
+}
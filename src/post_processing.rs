@@ -1,7 +1,7 @@
 // Import Bevy game engine essentials
 use bevy::{prelude::*, render::{extract_component::{ExtractComponentPlugin, UniformComponentPlugin, ComponentUniforms, ExtractComponent}, render_graph::{RenderGraphApp, ViewNodeRunner, NodeRunError, ViewNode, RenderGraphContext}, RenderApp, render_resource::{Operations, RenderPassColorAttachment, RenderPassDescriptor, BindGroupEntry, BindingResource, BindGroupDescriptor, PipelineCache, BindGroupLayout, Sampler, CachedRenderPipelineId, BindGroupLayoutDescriptor, BindGroupLayoutEntry, ShaderStages, BindingType, TextureSampleType, TextureViewDimension, SamplerBindingType, SamplerDescriptor, RenderPipelineDescriptor, FragmentState, ColorTargetState, TextureFormat, ColorWrites, PrimitiveState, MultisampleState, ShaderType}, view::ViewTarget, renderer::{RenderContext, RenderDevice}, texture::BevyDefault}, core_pipeline::{core_2d, fullscreen_vertex_shader::fullscreen_shader_vertex_state}, ecs::query::QueryItem};
 
-use crate::derivables::*;
+use crate::{derivables::*, star::star_repair};
 
 pub struct PostProcessingPlugin;
 
@@ -13,7 +13,7 @@ impl Plugin for PostProcessingPlugin {
 				UniformComponentPlugin::<PostProcessSettings>::default(),
 			))
 			.add_systems(Update, (
-				update_settings,
+				update_settings.after(star_repair),
 			))
 		;
 
@@ -206,7 +206,11 @@ pub struct PostProcessSettings {
 fn update_settings(
 	mut settings_query: Query<&mut PostProcessSettings>,
 	keyboard: Res<Input<KeyCode>>,
+	mech_on_star: Res<MechOnStar>,
 ) {
+	// Select buys a star shield while standing on a star instead,
+	// star_repair runs first so this is this frame's star
+	if mech_on_star.0.is_some() {return};
 	if keyboard.just_pressed(SELECT_BUTTON)
 	|| keyboard.just_pressed(ALT_SELECT_BUTTON)
 	|| keyboard.just_pressed(ALT_ALT_SELECT_BUTTON) {
@@ -0,0 +1,92 @@
+// Import Bevy game engine essentials
+use bevy::prelude::*;
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for camera feedback such as screen shake
+// and hit stop when something big happens
+pub struct ScreenEffectsPlugin;
+
+impl Plugin for ScreenEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_systems(Update, (
+				read_camera_events,
+				update_hit_stop.after(read_camera_events),
+				shake_camera.after(read_camera_events),
+			).run_if(in_state(GameState::Level))
+			.run_if(in_state(PauseState::Unpaused)))
+			.add_systems(OnEnter(PauseState::Paused), (
+				reset_camera_effects,
+			))
+			.add_systems(OnExit(GameState::Level), (
+				reset_camera_effects,
+			))
+		;
+	}
+}
+
+fn read_camera_events(
+	mut ev_r_camera: EventReader<CameraEvent>,
+	mut camera_effects: ResMut<CameraEffects>,
+	mut time: ResMut<Time>,
+	settings: Res<ScreenEffectSettings>,
+) {
+	for ev in ev_r_camera.iter() {
+		match *ev {
+			CameraEvent::Shake(trauma) => {
+				camera_effects.trauma = (camera_effects.trauma + trauma * settings.shake).clamp(0.0, 1.0);
+			},
+			CameraEvent::HitStop(duration) => {
+				// Overlapping hit stops don't stack, the longest one wins
+				if settings.hit_stop && duration > camera_effects.hit_stop.remaining_secs() {
+					camera_effects.hit_stop = Timer::from_seconds(duration, TimerMode::Once);
+					time.set_relative_speed(0.0);
+				}
+			},
+		}
+	}
+}
+
+// Game time is frozen during hit stop so the
+// timer has to be advanced with the real time
+fn update_hit_stop(
+	mut camera_effects: ResMut<CameraEffects>,
+	mut time: ResMut<Time>,
+) {
+	if camera_effects.hit_stop.finished() {return};
+	let delta = time.raw_delta();
+	camera_effects.hit_stop.tick(delta);
+	if camera_effects.hit_stop.just_finished() {
+		time.set_relative_speed(1.0);
+	}
+}
+
+fn shake_camera(
+	time: Res<Time>,
+	mut camera_effects: ResMut<CameraEffects>,
+	mut camera_query: Query<&mut Transform, With<MainCamera>>,
+) {
+	camera_effects.trauma = (camera_effects.trauma - TRAUMA_DECAY * time.raw_delta_seconds()).max(0.0);
+	// Squaring trauma makes small hits subtle and big hits violent
+	let shake = camera_effects.trauma * camera_effects.trauma * MAX_SHAKE_OFFSET;
+	for mut transform in camera_query.iter_mut() {
+		// Snap to whole pixels so sprites never land between pixels
+		transform.translation.x = ((rand::random::<f32>() - 0.5) * 2.0 * shake).round();
+		transform.translation.y = ((rand::random::<f32>() - 0.5) * 2.0 * shake).round();
+	}
+}
+
+fn reset_camera_effects(
+	mut camera_effects: ResMut<CameraEffects>,
+	mut time: ResMut<Time>,
+	mut camera_query: Query<&mut Transform, With<MainCamera>>,
+) {
+	camera_effects.trauma = 0.0;
+	camera_effects.hit_stop = Timer::from_seconds(0.0, TimerMode::Once);
+	time.set_relative_speed(1.0);
+	for mut transform in camera_query.iter_mut() {
+		transform.translation.x = 0.0;
+		transform.translation.y = 0.0;
+	}
+}
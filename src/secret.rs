@@ -0,0 +1,51 @@
+// Import Bevy game engine essentials
+use bevy::prelude::*;
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for watching button presses for secret sequences,
+// the systems that own each secret react to SecretEvent
+pub struct SecretPlugin;
+
+impl Plugin for SecretPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_systems( Update, (
+				track_secret_sequences,
+			))
+		;
+	}
+}
+
+fn track_secret_sequences(
+	keyboard: Res<Input<KeyCode>>,
+	game_state: Res<State<GameState>>,
+	mut secret_sequences: ResMut<SecretSequences>,
+	mut ev_w_secret: EventWriter<SecretEvent>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+) {
+	// Sequences have to be entered without leaving the screen
+	if game_state.is_changed() {
+		for sequence in secret_sequences.0.iter_mut() {
+			sequence.progress = 0;
+		}
+	}
+
+	let Some(input) = SecretInput::ALL.into_iter()
+		.find(|input| input.just_pressed(&keyboard)) else {return};
+
+	for sequence in secret_sequences.0.iter_mut() {
+		if sequence.state.is_some_and(|state| state != *game_state.get()) {continue};
+		if sequence.inputs[sequence.progress] == input {
+			sequence.progress += 1;
+		} else {
+			// A wrong press can still be the start of a new attempt
+			sequence.progress = if sequence.inputs[0] == input {1} else {0};
+		}
+		if sequence.progress == sequence.inputs.len() {
+			sequence.progress = 0;
+			ev_w_secret.send(SecretEvent(sequence.secret));
+			ev_w_sfx.send(SfxEvent::Secret);
+		}
+	}
+}
@@ -1,8 +1,7 @@
 // Import Bevy game engine essentials
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
 // Import components, resources, and events
-use crate::derivables::*;
+use crate::{derivables::*, menu_widget::navigate_menus};
 
 pub struct SelectPlugin;
 
@@ -13,7 +12,7 @@ impl Plugin for SelectPlugin {
 				spawn_select,
 			))
 			.add_systems(Update, (
-				navigate_select,
+				navigate_select.after(navigate_menus),
 			).run_if(in_state(GameState::LevelSelect)))
 		;
 	}
@@ -21,12 +20,10 @@ impl Plugin for SelectPlugin {
 
 fn spawn_select(
 	mut commands: Commands,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-	mut selected_level: ResMut<SelectedLevel>,
+	level_layout: Res<LevelLayout>,
+	hardcore: Res<Hardcore>,
 	asset_server: Res<AssetServer>,
 ) {
-	selected_level.0 = 0;
-	
 	commands
 		.spawn((SpriteBundle {
 			texture: asset_server.load("sprites/level_select.png"),
@@ -39,75 +36,70 @@ fn spawn_select(
 		DespawnOnExitGameState,
 	));
 
+	// One item per box on the level select screen, three to a row,
+	// a constellation with no stars can't be picked
+	let items = level_layout.constellations.iter().enumerate().map(|(index, constellation)| {
+		let min = Vec2::new(-66.0 + 50.0 * (index % 3) as f32, -8.0 - 50.0 * (index / 3) as f32);
+		MenuItem::new(Rect::from_corners(min, min + 36.0))
+			.with_label(&constellation.name)
+			.with_enabled(!constellation.stars.is_empty())
+	}).collect();
+	commands.spawn((
+		SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, 50.0)),
+		MenuWidget::new(items, 3, true),
+		SelectMenu,
+		DespawnOnExitGameState,
+	));
+
 	commands
-		.spawn((SpriteSheetBundle {
-			transform: Transform::from_xyz(0.0, 0.0, 50.0),
-			texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("sprites/select_highlight.png"), Vec2::new(ORTHO_WIDTH, ORTHO_HEIGHT), 3, 2, None, None)).clone(),
-			sprite: TextureAtlasSprite{
-				index: 0,
+		.spawn((SpriteBundle {
+			transform: Transform::from_xyz(0.0, 0.0, 60.0),
+			texture: asset_server.load("text/hardcore.png"),
+			sprite: Sprite {
 				custom_size: Some(Vec2::new(ORTHO_WIDTH, ORTHO_HEIGHT)),
 				..default()
 			},
+			visibility: if hardcore.0 {Visibility::Visible} else {Visibility::Hidden},
 			..default()
 		},
-		SelectHighlight,
+		HardcoreText,
 		DespawnOnExitGameState,
 	));
 }
 
 fn navigate_select(
 	keyboard: Res<Input<KeyCode>>,
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
 	mut endless: ResMut<Endless>,
+	mut hardcore: ResMut<Hardcore>,
 	mut selected_level: ResMut<SelectedLevel>,
-	mut highlight_query: Query<(&mut TextureAtlasSprite, With<SelectHighlight>)>,
+	menu_query: Query<(), With<SelectMenu>>,
+	mut hardcore_text_query: Query<(&mut Visibility, With<HardcoreText>)>,
 	mut next_game_state: ResMut<NextState<GameState>>,
+	mut ev_r_menu: EventReader<MenuEvent>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
 ) {
-	if keyboard.just_pressed(UP_BUTTON)
-	|| keyboard.just_pressed(ALT_UP_BUTTON) {
-		selected_level.0 = (selected_level.0 + 3) % 6;
-	} else if keyboard.just_pressed(DOWN_BUTTON)
-	|| keyboard.just_pressed(ALT_DOWN_BUTTON) {
-		selected_level.0 = (selected_level.0 + 3) % 6;
-	} else if keyboard.just_pressed(LEFT_BUTTON)
-	|| keyboard.just_pressed(ALT_LEFT_BUTTON) {
-		selected_level.0 = (selected_level.0 + 5) % 6;
-	} else if keyboard.just_pressed(RIGHT_BUTTON)
-	|| keyboard.just_pressed(ALT_RIGHT_BUTTON) {
-		selected_level.0 = (selected_level.0 + 1) % 6;
-	}
-
-	for (mut sprite, _) in highlight_query.iter_mut() {
-		sprite.index = selected_level.0;
-	}
-
-	if keyboard.just_pressed(A_BUTTON)
-	|| keyboard.just_pressed(ALT_A_BUTTON) {
-		if selected_level.0 == 5 {
-			endless.0 = true;
-		} else {
-			endless.0 = false;
+	for ev in ev_r_menu.iter().filter(|ev| menu_query.contains(ev.menu)) {
+		match ev.action {
+			MenuAction::Confirm(index) => {
+				selected_level.0 = index;
+				// The last constellation is endless mode
+				endless.0 = index == 5;
+				next_game_state.set(GameState::Loading);
+			}
+			MenuAction::Cancel => {
+				next_game_state.set(GameState::Menu);
+			}
 		}
-		next_game_state.set(GameState::Loading);
-	} else if keyboard.just_pressed(B_BUTTON)
-	|| keyboard.just_pressed(ALT_B_BUTTON) {
-		next_game_state.set(GameState::Menu);
 	}
 
-	if keyboard.just_pressed(A_BUTTON)
-	|| keyboard.just_pressed(ALT_A_BUTTON)
-	|| keyboard.just_pressed(B_BUTTON)
-	|| keyboard.just_pressed(ALT_B_BUTTON)
-	|| keyboard.just_pressed(UP_BUTTON)
-	|| keyboard.just_pressed(ALT_UP_BUTTON)
-	|| keyboard.just_pressed(DOWN_BUTTON)
-	|| keyboard.just_pressed(ALT_DOWN_BUTTON)
-	|| keyboard.just_pressed(LEFT_BUTTON)
-	|| keyboard.just_pressed(ALT_LEFT_BUTTON)
-	|| keyboard.just_pressed(RIGHT_BUTTON)
-	|| keyboard.just_pressed(ALT_RIGHT_BUTTON)
-	{
-		audio.play(asset_server.load("sfx/ui_select.ogg")).with_volume(SFX_VOLUME);
+	// Start toggles hardcore mode for the next run
+	if keyboard.just_pressed(START_BUTTON)
+	|| keyboard.just_pressed(ALT_START_BUTTON)
+	|| keyboard.just_pressed(ALT_ALT_START_BUTTON) {
+		hardcore.0 = !hardcore.0;
+		for (mut visibility, _) in hardcore_text_query.iter_mut() {
+			*visibility = if hardcore.0 {Visibility::Visible} else {Visibility::Hidden};
+		}
+		ev_w_sfx.send(SfxEvent::Secret);
 	}
-}
\ No newline at end of file
+}
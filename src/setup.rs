@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, SeedableRng};
+
 // Import Bevy game engine essentials
-use bevy::{prelude::*, core_pipeline::clear_color::ClearColorConfig, render::camera::ScalingMode};
+use bevy::{prelude::*, asset::LoadState, core_pipeline::clear_color::ClearColorConfig, render::camera::ScalingMode};
 // Import components, resources, and events
 use crate::{derivables::*, post_processing::PostProcessSettings};
 
@@ -15,33 +19,66 @@ impl Plugin for SetupPlugin {
 			.add_state::<GameState>()
 			.add_state::<PauseState>()
 			// Events
-
+			.add_event::<SfxEvent>()
+			.add_event::<ParticleEvent>()
+			.add_event::<CameraEvent>()
+			.add_event::<EnemyDefeated>()
+			.add_event::<SecretEvent>()
+			.add_event::<MenuEvent>()
 			// Resources
 			.insert_resource(Milky(false))
 			.insert_resource(Endless(false))
 			.insert_resource(LoadTimes(true))
-			.insert_resource(SecretCode(0))
+			.insert_resource(SecretSequences::new(&SECRET_SEQUENCES))
 			.insert_resource(Retaliate(true))
+			.insert_resource(Hardcore(false))
+			.insert_resource(EightWay(false))
+			.insert_resource(Drift(false))
 			.insert_resource(BeamCharge(0.0))
 			.insert_resource(SelectedLevel(0))
 			.insert_resource(EnemiesSlain(0))
 			.insert_resource(WinSpawned(0))
 			.insert_resource(WinState(0))
-			.insert_resource(LoadingLength{
-				total: 4,
-				current: 0,
+			.insert_resource(MechOnStar(None))
+			.insert_resource(FlockingSettings{
+				separation_radius: ENEMY_SEPARATION_RADIUS,
+				alignment_radius: ENEMY_ALIGNMENT_RADIUS,
+			})
+			.insert_resource(EnemyGrid{
+				cell_size: ENEMY_ALIGNMENT_RADIUS,
+				cells: HashMap::new(),
+			})
+			.insert_resource(ScreenEffectSettings{
+				shake: 1.0,
+				hit_stop: true,
+			})
+			.insert_resource(CameraEffects{
+				trauma: 0.0,
+				hit_stop: Timer::from_seconds(0.0, TimerMode::Once),
+			})
+			.insert_resource(LoadingProgress{
+				total: 0,
+				loaded: 0,
+				failed: 0,
 			})
 			.insert_resource(DustTimer(Timer::from_seconds(DUST_COOLDOWN, TimerMode::Repeating)))
 			.insert_resource(BootTimer(Timer::from_seconds(BOOT_DURATION, TimerMode::Once)))
 			.insert_resource(StartTimer(Timer::from_seconds(START_DURATION, TimerMode::Repeating)))
-			.insert_resource(LoadingTimer(Timer::from_seconds(LOADING_DURATION, TimerMode::Repeating)))
 			.insert_resource(NoEnemies(Timer::from_seconds(NO_ENEMIES_DURATION, TimerMode::Repeating)))
 			.insert_resource(WinTimer(Timer::from_seconds(WIN_DURATION, TimerMode::Repeating)))
+			.insert_resource(IncomingEnemies(Vec::new()))
+			.insert_resource(GameRng(StdRng::from_entropy()))
+			.insert_resource(GameTuning::default())
 			.insert_resource(LevelInfo{
 				round_timer: Timer::from_seconds(ENEMY_SPAWN_DELAY, TimerMode::Repeating),
 				round: 0,
+				boss_spawned: false,
+				boss_defeated: false,
 			})
 			// Systems
+			.add_systems( PreStartup,(
+				init_sprite_atlases,
+			))
 			.add_systems( Startup,(
 				spawn_camera,
 				spawn_splash_screen,
@@ -72,6 +109,9 @@ impl Plugin for SetupPlugin {
 			.add_systems(OnExit(GameState::Win), (
 				despawn_entities_with::<DespawnOnExitGameState>,
 			))
+			.add_systems(OnExit(GameState::Editor), (
+				despawn_entities_with::<DespawnOnExitGameState>,
+			))
 		;
 	}
 }
@@ -99,6 +139,7 @@ fn spawn_camera(
             intensity: 0.0,
             ..default()
         },
+		MainCamera,
 	));
 }
 
@@ -121,11 +162,16 @@ fn spawn_splash_screen(
 }
 
 // Fade transitions into menu after a certain amount 
-// of time or when the user presses a button
+// of time or when the user presses a button, but not before
+// the level file and catalogue the menus are built from are in
 fn advance_splash_screen(
 	keyboard: Res<Input<KeyCode>>,
 	time: Res<Time>,
+	asset_server: Res<AssetServer>,
+	layout_handle: Res<LevelLayoutHandle>,
+	catalogue_handle: Res<StarCatalogueHandle>,
 	mut boot_timer: ResMut<BootTimer>,
+	mut skipped: Local<bool>,
 	mut next_game_state: ResMut<NextState<GameState>>,
 ) {
 	boot_timer.0.tick(time.delta());
@@ -133,92 +179,83 @@ fn advance_splash_screen(
 	|| keyboard.just_pressed(ALT_START_BUTTON)
 	|| keyboard.just_pressed(ALT_ALT_START_BUTTON)
 	|| keyboard.just_pressed(A_BUTTON)
-	|| keyboard.just_pressed(ALT_A_BUTTON)
-	|| boot_timer.0.just_finished() {
+	|| keyboard.just_pressed(ALT_A_BUTTON) {
+		*skipped = true;
+	}
+	// A file that failed to load won't arrive by waiting longer
+	let ready = [layout_handle.0.id(), catalogue_handle.0.id()].into_iter().all(|handle_id| {
+		matches!(asset_server.get_load_state(handle_id), LoadState::Loaded | LoadState::Failed)
+	});
+	if ready && (*skipped || boot_timer.0.finished()) {
 		next_game_state.set(GameState::Menu);
 	}
 }
 
+// Level select slots, filled in once the level file loads
+// and the menu has generated the random constellation
 fn init_constellations(
 	mut commands: Commands,
-	mut milky: ResMut<Milky>,
 ) {
-	let mut constellations = Vec::new(); 
-	let mut cassiopeia = Vec::new();
-	cassiopeia.push(Vec2::new(-44.5, 18.5));
-	cassiopeia.push(Vec2::new(-24.5, -16.5));
-	cassiopeia.push(Vec2::new(8.5, 0.5));
-	cassiopeia.push(Vec2::new(26.5, -34.5));
-	cassiopeia.push(Vec2::new(46.5, 2.5));
-
-	let mut cepheus = Vec::new();
-	cepheus.push(Vec2::new(-10.5, 40.5));
-	cepheus.push(Vec2::new(-38.5, 8.5));
-	cepheus.push(Vec2::new(-40.5, -50.5));
-	cepheus.push(Vec2::new(14.5, -12.5));
-	cepheus.push(Vec2::new(30.5, 26.5));
-
-	let mut ursa_minor = Vec::new();
-	ursa_minor.push(Vec2::new(18.5, 40.5));
-	ursa_minor.push(Vec2::new(40.5, 28.5));
-	ursa_minor.push(Vec2::new(30.5, -2.5));
-	ursa_minor.push(Vec2::new(4.5, 4.5));
-	ursa_minor.push(Vec2::new(-16.5, -8.5));
-	ursa_minor.push(Vec2::new(-26.5, -26.5));
-	ursa_minor.push(Vec2::new(-24.5, -52.5));
-
-	let mut orion = Vec::new();
-	orion.push(Vec2::new(-40.5, 40.5));
-	orion.push(Vec2::new(-6.5, 34.5));
-	orion.push(Vec2::new(-48.5, 6.5));
-	orion.push(Vec2::new(6.5, 2.5));
-	orion.push(Vec2::new(-2.5, -10.5));
-	orion.push(Vec2::new(-12.5, -22.5));
-	orion.push(Vec2::new(46.5, -14.5));
-	orion.push(Vec2::new(16.5, -58.5));
-
-	let mut random = Vec::new();
-	if rand::random::<f32>() < 0.95 { 
-		let total_stars = rand::Rng::gen_range(&mut rand::thread_rng(), 2..9);
-		for _ in 0..total_stars {
-			random.push(Vec2::new(((rand::random::<f32>() - 0.5) * 120.0).round() + 0.5, ((rand::random::<f32>() - 0.5) * 80.0).round() + 0.5));
-		}
-		milky.0 = false;
-	} else {
-		// The Sun
-		random.push(Vec2::new(0.5, 0.5));
-		milky.0 = true;
-	}
-
-	let endless = Vec::new();
-
-	constellations.push(cassiopeia);
-	constellations.push(cepheus);
-	constellations.push(ursa_minor);
-	constellations.push(orion);
-	constellations.push(random);
-	constellations.push(endless);
+	commands.insert_resource(LevelLayout{
+		constellations: vec![Constellation::default(); LEVEL_SLOTS],
+	});
+}
 
-	let mut level_layout = LevelLayout{
-		constellations: Vec::new(),
+// Build every sprite sheet atlas once so spawning sprites
+// doesn't create a new atlas asset each time
+fn init_sprite_atlases(
+	mut commands: Commands,
+	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	asset_server: Res<AssetServer>,
+) {
+	let mut atlas = |path: &str, tile_size: Vec2, columns: usize, rows: usize| {
+		texture_atlases.add(TextureAtlas::from_grid(asset_server.load(path), tile_size, columns, rows, None, None))
 	};
-
-	for constellation in constellations {
-		level_layout.constellations.push(constellation);
-	}
-
-	commands.insert_resource(level_layout);
+	commands.insert_resource(SpriteAtlases{
+		mech: atlas("sprites/mech.png", Vec2::new(16.0, 16.0), 4, 4),
+		forward_slash: atlas("sprites/forward_slash.png", Vec2::new(24.0, 16.0), 4, 2),
+		backward_slash: atlas("sprites/backward_slash.png", Vec2::new(24.0, 16.0), 4, 2),
+		left_slash: atlas("sprites/left_slash.png", Vec2::new(16.0, 24.0), 4, 2),
+		right_slash: atlas("sprites/right_slash.png", Vec2::new(16.0, 24.0), 4, 2),
+		beam_down: atlas("sprites/beam_down.png", Vec2::new(60.0, 144.0), 4, 2),
+		beam_up: atlas("sprites/beam_up.png", Vec2::new(60.0, 144.0), 4, 2),
+		beam_left: atlas("sprites/beam_left.png", Vec2::new(144.0, 60.0), 2, 4),
+		beam_right: atlas("sprites/beam_right.png", Vec2::new(144.0, 60.0), 2, 4),
+		boss: atlas("sprites/boss.png", Vec2::new(32.0, 32.0), 4, 1),
+		weak_point: atlas("sprites/weak_point.png", Vec2::new(8.0, 8.0), 3, 1),
+		spawn_arrow: atlas("sprites/spawn_arrow.png", Vec2::new(8.0, 8.0), 8, 1),
+		digits: atlas("sprites/digits.png", Vec2::new(4.0, 6.0), 10, 1),
+		font: atlas("sprites/debug_font.png", Vec2::new(4.0, 6.0), 16, 4),
+		enemies: vec![
+			atlas("sprites/enemy_0.png", Vec2::new(16.0, 16.0), 2, 2),
+			atlas("sprites/enemy_1.png", Vec2::new(16.0, 16.0), 2, 2),
+			atlas("sprites/enemy_2.png", Vec2::new(16.0, 16.0), 2, 2),
+		],
+		corpse: atlas("sprites/corpse.png", Vec2::new(8.0, 8.0), 4, 2),
+		dust: atlas("sprites/dust.png", Vec2::new(8.0, 8.0), 2, 2),
+		pickup: atlas("sprites/pickup.png", Vec2::new(8.0, 8.0), 6, 1),
+		star: atlas("sprites/star.png", Vec2::new(15.0, 15.0), 4, 2),
+		background: atlas("sprites/background.png", Vec2::new(160.0, 144.0), 3, 2),
+		beam_bar: atlas("sprites/beam_bar.png", Vec2::new(80.0, 20.0), 2, 4),
+		hints: atlas("text/hints.png", Vec2::new(140.0, 120.0), 4, 7),
+		loading_text: atlas("text/loading.png", Vec2::new(160.0, 144.0), 5, 1),
+		retaliation_text: atlas("text/retaliation.png", Vec2::new(92.0, 12.0), 1, 2),
+		controls_text: atlas("text/controls.png", Vec2::new(56.0, 8.0), 1, 2),
+		movement_text: atlas("text/movement.png", Vec2::new(56.0, 8.0), 1, 2),
+		lost_text: atlas("text/lost.png", Vec2::new(160.0, 144.0), 4, 1),
+		constellation_text: atlas("text/constellation.png", Vec2::new(160.0, 144.0), 4, 1),
+		win_text: atlas("text/win.png", Vec2::new(160.0, 144.0), 4, 1),
+	});
 }
 
 fn spawn_hints(
-	asset_server: Res<AssetServer>,
 	mut commands: Commands,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	sprite_atlases: Res<SpriteAtlases>,
 ) {
 	commands
 		.spawn((SpriteSheetBundle {
 			transform: Transform::from_xyz(0.0, 0.0, 400.0),
-			texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("text/hints.png"), Vec2::new(140.0, 120.0), 4, 7, None, None)).clone(),
+			texture_atlas: sprite_atlases.hints.clone(),
 			sprite: TextureAtlasSprite{
 				index: 0,
 				custom_size: Some(Vec2::new(140.0, 120.0)),
@@ -0,0 +1,171 @@
+// Import Bevy game engine essentials
+use bevy::{prelude::*, math::Vec3Swizzles};
+// Import components, resources, and events
+use crate::{derivables::*, enemy::enemy_move};
+
+// Plugin for everything the player can do to keep
+// stars alive besides killing the enemies around them
+pub struct StarPlugin;
+
+impl Plugin for StarPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_systems(Update, (
+				star_shield,
+				star_regenerate.after(enemy_move),
+				star_repair.after(enemy_move),
+				star_last_stand.after(enemy_move),
+			).run_if(in_state(GameState::Level))
+			.run_if(in_state(PauseState::Unpaused)))
+			.add_systems(OnEnter(PauseState::Paused), (
+				clear_mech_on_star,
+			))
+			.add_systems(OnExit(GameState::Level), (
+				clear_mech_on_star,
+			))
+		;
+	}
+}
+
+fn star_shield(
+	time: Res<Time>,
+	mut shimmer: Local<f32>,
+	mut star_query: Query<(&Transform, &mut Star)>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+) {
+	*shimmer += time.delta_seconds();
+	let emit = *shimmer >= STAR_SHIELD_SHIMMER;
+	if emit {*shimmer = 0.0};
+	for (transform, mut star) in star_query.iter_mut() {
+		if star.shielded() {
+			star.shield = (star.shield - time.delta_seconds()).max(0.0);
+			if emit {
+				ev_w_particles.send(ParticleEvent{
+					effect: ParticleEffect::StarShield,
+					position: transform.translation.xy(),
+					direction: Vec2::ZERO,
+				});
+			}
+		}
+	}
+}
+
+// Stars slowly recover as long as no enemies are feeding on them
+fn star_regenerate(
+	time: Res<Time>,
+	tuning: Res<GameTuning>,
+	enemy_query: Query<&TruePosition, With<Enemy>>,
+	mut star_query: Query<(&Transform, &mut Star)>,
+) {
+	for (transform, mut star) in star_query.iter_mut() {
+		let star_pos = transform.translation.xy();
+		let adjacent = enemy_query.iter().any(|enemy_pos| (enemy_pos.0 - star_pos).length() < STAR_ADJACENT_DISTANCE);
+		if !adjacent {
+			star.heal(tuning.star_regen_rate * time.delta_seconds());
+		}
+	}
+}
+
+// Standing on a star repairs it, and pressing select
+// while there spends beam charge on a temporary shield
+pub fn star_repair(
+	time: Res<Time>,
+	keyboard: Res<Input<KeyCode>>,
+	tuning: Res<GameTuning>,
+	mech_query: Query<&TruePosition, With<Mech>>,
+	mut star_query: Query<(Entity, &Transform, &mut Star)>,
+	mut beam_charge: ResMut<BeamCharge>,
+	mut mech_on_star: ResMut<MechOnStar>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+) {
+	let mut on_star = None;
+	for mech_pos in mech_query.iter() {
+		for (entity, transform, mut star) in star_query.iter_mut() {
+			if (transform.translation.xy() - mech_pos.0).length() < STAR_REPAIR_DISTANCE {
+				on_star = Some(entity);
+				star.heal(tuning.star_repair_rate * time.delta_seconds());
+				if (keyboard.just_pressed(SELECT_BUTTON)
+				|| keyboard.just_pressed(ALT_SELECT_BUTTON)
+				|| keyboard.just_pressed(ALT_ALT_SELECT_BUTTON))
+				&& beam_charge.0 >= tuning.star_shield_cost
+				&& !star.shielded() {
+					beam_charge.0 -= tuning.star_shield_cost;
+					star.shield = tuning.star_shield_duration;
+					ev_w_sfx.send(SfxEvent::Secret);
+				}
+				break;
+			}
+		}
+	}
+	if mech_on_star.0 != on_star {
+		mech_on_star.0 = on_star;
+	}
+}
+
+// The first time a star drops low it lashes out,
+// destroying every enemy close to it
+fn star_last_stand(
+	mut commands: Commands,
+	mut enemies_slain: ResMut<EnemiesSlain>,
+	mut star_query: Query<(&Transform, &mut Star)>,
+	mut ev_w_sfx: EventWriter<SfxEvent>,
+	mut ev_w_particles: EventWriter<ParticleEvent>,
+	mut ev_w_defeated: EventWriter<EnemyDefeated>,
+	mut ev_w_camera: EventWriter<CameraEvent>,
+	sprite_atlases: Res<SpriteAtlases>,
+	enemy_query: Query<(Entity, &TruePosition), With<Enemy>>,
+) {
+	// Enemies near more than one star are only killed once
+	let mut killed = Vec::new();
+	for (transform, mut star) in star_query.iter_mut() {
+		// Fully repaired stars get their last stand back
+		if star.health >= star.max_health {
+			star.last_stand = false;
+		}
+		if star.last_stand || star.health <= 0.0 || star.health >= STAR_LAST_STAND_HEALTH {continue};
+		star.last_stand = true;
+		let star_pos = transform.translation.xy();
+		for (entity, enemy_pos) in enemy_query.iter() {
+			if !killed.contains(&entity) && (enemy_pos.0 - star_pos).length() < STAR_LAST_STAND_RADIUS {
+				killed.push(entity);
+				enemies_slain.0 += 1;
+				commands.entity(entity).despawn_recursive();
+				commands
+					.spawn((SpriteSheetBundle {
+						transform: Transform::from_xyz(enemy_pos.0.x.round(), enemy_pos.0.y.round(), 125.0),
+						texture_atlas: sprite_atlases.corpse.clone(),
+						sprite: TextureAtlasSprite{
+							index: 0,
+							custom_size: Some(Vec2::new(8.0, 8.0)),
+							..default()
+						},
+						..default()
+					},
+					Corpse(Timer::from_seconds(CORPSE_DURATION, TimerMode::Repeating)),
+					DespawnOnExitGameState,
+				));
+				ev_w_particles.send(ParticleEvent{
+					effect: ParticleEffect::EnemyBurst,
+					position: enemy_pos.0,
+					direction: Vec2::ZERO,
+				});
+				ev_w_defeated.send(EnemyDefeated{
+					position: enemy_pos.0,
+				});
+			}
+		}
+		ev_w_particles.send(ParticleEvent{
+			effect: ParticleEffect::StarSpark,
+			position: star_pos,
+			direction: Vec2::ZERO,
+		});
+		ev_w_sfx.send(SfxEvent::EnemyDestroyed);
+		ev_w_camera.send(CameraEvent::Shake(STAR_LAST_STAND_TRAUMA));
+	}
+}
+
+fn clear_mech_on_star(
+	mut mech_on_star: ResMut<MechOnStar>,
+) {
+	mech_on_star.0 = None;
+}
@@ -0,0 +1,115 @@
+// Import Bevy game engine essentials
+use bevy::prelude::*;
+
+// Steering primitives for enemy movement. Each one only looks at
+// the positions it is given and returns a direction no longer than
+// one, so they can be weighted and added together per archetype
+
+// Head straight for a target
+pub fn seek(from: Vec2, target: Vec2) -> Vec2 {
+	(target - from).normalize_or_zero()
+}
+
+// Run from a threat, but only once it gets within the radius
+pub fn flee(from: Vec2, threat: Vec2, radius: f32) -> Vec2 {
+	if (from - threat).length() < radius {(from - threat).normalize_or_zero()} else {Vec2::ZERO}
+}
+
+// Circle around a point while within the radius,
+// rotation is 1 for clockwise and -1 for anticlockwise
+pub fn orbit(from: Vec2, centre: Vec2, radius: f32, rotation: f32) -> Vec2 {
+	if (from - centre).length() < radius {(centre - from).normalize_or_zero().perp() * rotation} else {Vec2::ZERO}
+}
+
+// Push away from every neighbour within the radius,
+// closer neighbours push harder
+pub fn separate(from: Vec2, neighbours: impl Iterator<Item = Vec2>, radius: f32) -> Vec2 {
+	let mut push = Vec2::ZERO;
+	for neighbour in neighbours {
+		let offset = from - neighbour;
+		let distance = offset.length();
+		// Skips itself as well as anything out of range
+		if distance > 0.0 && distance < radius {
+			push += offset / distance * (1.0 - distance / radius);
+		}
+	}
+	push.clamp_length_max(1.0)
+}
+
+// Match the average heading of nearby neighbours
+pub fn align(neighbour_velocities: impl Iterator<Item = Vec2>) -> Vec2 {
+	neighbour_velocities.fold(Vec2::ZERO, |sum, velocity| sum + velocity.normalize_or_zero()).normalize_or_zero()
+}
+
+// Cancel out the current velocity to come to a stop
+pub fn brake(velocity: Vec2, acceleration: f32) -> Vec2 {
+	-velocity.clamp_length_max(acceleration) / acceleration
+}
+
+// Targets are tagged with whatever identifies them, such as their
+// entity, which is handed back along with the chosen position
+pub fn nearest<T: Copy>(from: Vec2, targets: &[(T, Vec2)]) -> Option<(T, Vec2)> {
+	targets.iter().copied().min_by(|a, b| (a.1 - from).length().total_cmp(&(b.1 - from).length()))
+}
+
+// Flanking goes for whichever target is furthest from the threat
+pub fn flank<T: Copy>(threat: Vec2, targets: &[(T, Vec2)]) -> Option<(T, Vec2)> {
+	targets.iter().copied().max_by(|a, b| (a.1 - threat).length().total_cmp(&(b.1 - threat).length()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn seek_points_at_the_target() {
+		assert_eq!(seek(Vec2::ZERO, Vec2::new(0.0, 5.0)), Vec2::Y);
+		assert_eq!(seek(Vec2::ONE, Vec2::ONE), Vec2::ZERO);
+	}
+
+	#[test]
+	fn flee_only_within_the_radius() {
+		assert_eq!(flee(Vec2::new(2.0, 0.0), Vec2::ZERO, 5.0), Vec2::X);
+		assert_eq!(flee(Vec2::new(6.0, 0.0), Vec2::ZERO, 5.0), Vec2::ZERO);
+	}
+
+	#[test]
+	fn orbit_turns_the_given_way() {
+		// Due east of the centre, clockwise heads south
+		assert_eq!(orbit(Vec2::new(2.0, 0.0), Vec2::ZERO, 5.0, 1.0), Vec2::NEG_Y);
+		assert_eq!(orbit(Vec2::new(2.0, 0.0), Vec2::ZERO, 5.0, -1.0), Vec2::Y);
+		assert_eq!(orbit(Vec2::new(9.0, 0.0), Vec2::ZERO, 5.0, 1.0), Vec2::ZERO);
+	}
+
+	#[test]
+	fn separate_pushes_harder_when_closer() {
+		let near = separate(Vec2::ZERO, [Vec2::new(1.0, 0.0)].into_iter(), 4.0);
+		let far = separate(Vec2::ZERO, [Vec2::new(3.0, 0.0)].into_iter(), 4.0);
+		assert!(near.x < far.x && far.x < 0.0);
+		assert_eq!(near.y, 0.0);
+		// Itself and anything out of range are ignored
+		assert_eq!(separate(Vec2::ZERO, [Vec2::ZERO, Vec2::new(5.0, 0.0)].into_iter(), 4.0), Vec2::ZERO);
+		assert!(separate(Vec2::ZERO, [Vec2::new(0.1, 0.0); 10].into_iter(), 4.0).length() <= 1.0);
+	}
+
+	#[test]
+	fn align_averages_headings() {
+		let heading = align([Vec2::new(10.0, 0.0), Vec2::new(0.0, 1.0)].into_iter());
+		assert!((heading - Vec2::new(1.0, 1.0).normalize()).length() < 1e-6);
+		assert_eq!(align(std::iter::empty()), Vec2::ZERO);
+	}
+
+	#[test]
+	fn brake_opposes_velocity() {
+		assert_eq!(brake(Vec2::new(2.0, 0.0), 4.0), Vec2::new(-0.5, 0.0));
+		assert_eq!(brake(Vec2::new(0.0, 8.0), 4.0), Vec2::NEG_Y);
+	}
+
+	#[test]
+	fn nearest_and_flank_return_the_tag() {
+		let targets = [(0, Vec2::new(1.0, 0.0)), (1, Vec2::new(10.0, 0.0))];
+		assert_eq!(nearest(Vec2::ZERO, &targets), Some(targets[0]));
+		assert_eq!(flank(Vec2::ZERO, &targets), Some(targets[1]));
+		assert_eq!(nearest::<usize>(Vec2::ZERO, &[]), None);
+	}
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+// Import Bevy game engine essentials
+use bevy::{prelude::*, asset::{AssetLoader, LoadContext, LoadedAsset, BoxedFuture}};
+// Import components, resources, and events
+use crate::derivables::*;
+
+// Plugin for loading balance numbers from a file, changes
+// are picked up while the game runs when watching is on
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app
+			.add_asset::<GameTuning>()
+			.init_asset_loader::<GameTuningLoader>()
+			.add_systems(Startup, (
+				load_tuning,
+			))
+			.add_systems(Update, (
+				apply_tuning,
+			))
+		;
+	}
+}
+
+#[derive(Default)]
+struct GameTuningLoader;
+
+impl AssetLoader for GameTuningLoader {
+	fn load<'a>(
+		&'a self,
+		bytes: &'a [u8],
+		load_context: &'a mut LoadContext,
+	) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+		Box::pin(async move {
+			let tuning: GameTuning = ron::de::from_bytes(bytes)?;
+			load_context.set_default_asset(LoadedAsset::new(tuning));
+			Ok(())
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["tuning.ron"]
+	}
+}
+
+fn load_tuning(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+) {
+	commands.insert_resource(TuningHandle(asset_server.load("balance.tuning.ron")));
+}
+
+// Copies the file into the resource whenever it loads or changes,
+// timers that already exist get their new durations straight away
+fn apply_tuning(
+	mut ev_r_tuning: EventReader<AssetEvent<GameTuning>>,
+	tuning_assets: Res<Assets<GameTuning>>,
+	tuning_handle: Res<TuningHandle>,
+	mut tuning: ResMut<GameTuning>,
+	mut level_info: ResMut<LevelInfo>,
+	mut mech_query: Query<&mut Mech>,
+	mut edge_query: Query<&mut Edge>,
+	mut enemy_query: Query<(&mut Enemy, Option<&mut Shooter>)>,
+) {
+	for event in ev_r_tuning.iter() {
+		match event {
+			AssetEvent::Created { handle } | AssetEvent::Modified { handle } if *handle == tuning_handle.0 => {
+				let Some(loaded) = tuning_assets.get(handle) else {continue};
+				*tuning = loaded.clone();
+				level_info.round_timer.set_duration(Duration::from_secs_f32(tuning.enemy_spawn_delay));
+				for mut mech in mech_query.iter_mut() {
+					mech.stun_cooldown.set_duration(Duration::from_secs_f32(tuning.stun_cooldown));
+					mech.slash_cooldown.set_duration(Duration::from_secs_f32(tuning.slash_cooldown));
+					mech.shoot_cooldown.set_duration(Duration::from_secs_f32(tuning.shoot_cooldown));
+					mech.beam_cooldown.set_duration(Duration::from_secs_f32(tuning.beam_cooldown));
+					mech.dash_cooldown.set_duration(Duration::from_secs_f32(tuning.dash_cooldown));
+				}
+				for mut edge in edge_query.iter_mut() {
+					edge.zap_cooldown.set_duration(Duration::from_secs_f32(tuning.edge_zap_cooldown));
+				}
+				// Enemies copy their numbers when they spawn so they need updating too
+				for (mut enemy, shooter) in enemy_query.iter_mut() {
+					enemy.dps = tuning.enemy_dps[enemy.spec];
+					enemy.range = tuning.enemy_range[enemy.spec];
+					if let Some(mut shooter) = shooter {
+						shooter.0.set_duration(Duration::from_secs_f32(tuning.enemy_fire_rate));
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+}
@@ -22,6 +22,9 @@ impl Plugin for WinPlugin {
 fn spawn_win_screen(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
+	sprite_atlases: Res<SpriteAtlases>,
+	level_info: Res<LevelInfo>,
+	win_state: Res<WinState>,
 ) {
 	commands.spawn((
 		SpriteBundle{
@@ -35,6 +38,37 @@ fn spawn_win_screen(
 		},
 		DespawnOnExitGameState,
 	));
+
+	// Defending a constellation means beating its boss,
+	// this stays up over every page of the win text
+	if win_state.0 != 1 || !level_info.boss_defeated {return};
+	commands.spawn((
+		SpriteBundle{
+			transform: Transform::from_xyz(0.0, 64.0, 300.0),
+			sprite: Sprite {
+				color: Color::BLACK,
+				custom_size: Some(Vec2::new(4.0 * BOSS_DEFEATED_TEXT.len() as f32 + 4.0, 10.0)),
+				..default()
+			},
+			..default()
+		},
+		DespawnOnExitGameState,
+	));
+	for (column, character) in BOSS_DEFEATED_TEXT.chars().enumerate() {
+		commands.spawn((
+			SpriteSheetBundle {
+				transform: Transform::from_xyz(-2.0 * BOSS_DEFEATED_TEXT.len() as f32 + 2.0 + 4.0 * column as f32, 64.0, 310.0),
+				texture_atlas: sprite_atlases.font.clone(),
+				sprite: TextureAtlasSprite{
+					index: FONT_CHARS.find(character).unwrap_or(0),
+					custom_size: Some(Vec2::new(4.0, 6.0)),
+					..default()
+				},
+				..default()
+			},
+			DespawnOnExitGameState,
+		));
+	}
 }
 
 fn update_win_timer(
@@ -42,11 +76,10 @@ fn update_win_timer(
 	mut win_spawned: ResMut<WinSpawned>,
 	mut win_timer: ResMut<WinTimer>,
 	mut clock_divider: Local<bool>,
-	mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+	sprite_atlases: Res<SpriteAtlases>,
 	mut win_text_query: Query<(Entity, &mut Visibility, With<WinText>)>,
 	mut next_game_state: ResMut<NextState<GameState>>,
 	keyboard: Res<Input<KeyCode>>,
-	asset_server: Res<AssetServer>,
 	win_state: Res<WinState>,
 	time: Res<Time>,
 ) {
@@ -66,7 +99,7 @@ fn update_win_timer(
 			commands
 				.spawn((SpriteSheetBundle {
 					transform: Transform::from_xyz(0.0, 0.0, 200.0),
-					texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("text/lost.png"), Vec2::new(160.0, 144.0), 3, 1, None, None)).clone(),
+					texture_atlas: sprite_atlases.lost_text.clone(),
 					sprite: TextureAtlasSprite{
 						index: win_state.0,
 						custom_size: Some(Vec2::new(160.0, 144.0)),
@@ -82,7 +115,7 @@ fn update_win_timer(
 			commands
 				.spawn((SpriteSheetBundle {
 					transform: Transform::from_xyz(0.0, 0.0, 200.0),
-					texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("text/constellation.png"), Vec2::new(160.0, 144.0), 3, 1, None, None)).clone(),
+					texture_atlas: sprite_atlases.constellation_text.clone(),
 					sprite: TextureAtlasSprite{
 						index: win_state.0,
 						custom_size: Some(Vec2::new(160.0, 144.0)),
@@ -101,7 +134,7 @@ fn update_win_timer(
 			commands
 				.spawn((SpriteSheetBundle {
 					transform: Transform::from_xyz(0.0, 0.0, 200.0),
-					texture_atlas: texture_atlases.add(TextureAtlas::from_grid(asset_server.load("text/win.png"), Vec2::new(160.0, 144.0), 3, 1, None, None)).clone(),
+					texture_atlas: sprite_atlases.win_text.clone(),
 					sprite: TextureAtlasSprite{
 						index: win_state.0,
 						custom_size: Some(Vec2::new(160.0, 144.0)),